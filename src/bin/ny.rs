@@ -1,60 +1,247 @@
 use std::env;
+use std::path::Path;
 extern crate common;
-use eyre::{eyre, Result};
+use eyre::{eyre, Context, Result};
 
 use common::{
     agent::Agent,
-    cli::{parse_from, Commands},
+    bootstrap::BootstrapExecutor,
+    cli::{parse_with_scripts, Commands, PkgCommands, TypesCommands},
     commands::{
         add::add,
+        completions::{complete, completions},
+        doctor::doctor,
         install::install,
         install_ts_types::{check_if_ts_repo, install_ts_types},
+        lint_scripts::lint_scripts,
+        migrate::migrate,
+        outdated::outdated,
+        pkg::{pkg_delete, pkg_get, pkg_set},
         run::run,
+        shim::shim,
+        sync_ts_types::sync_ts_types,
+        which::which,
+        why::why,
     },
-    execute::RealExecutor,
-    fs::RealFs,
+    config::{user_config_dir, Settings},
+    execute::{DryRunExecutor, Executor, RealExecutor},
+    fs::{find_in_parents, RealFs},
     http::RealHttpClient,
+    manifest::PackageManifest,
 };
 
 fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
-    let cli = parse_from(args);
-    let cwd = env::current_dir().unwrap();
-    let executor = RealExecutor {};
+    let cli = parse_with_scripts(args, has_script);
+    let cwd = match &cli.cwd {
+        Some(dir) => env::current_dir()?
+            .join(dir)
+            .canonicalize()
+            .with_context(|| format!("Couldn't access directory: {dir:?}"))?,
+        None => env::current_dir()?,
+    };
+    let executor: Box<dyn Executor> = if cli.dry_run {
+        Box::new(DryRunExecutor {
+            cwd: Some(cwd.clone()),
+        })
+    } else {
+        Box::new(RealExecutor {
+            cwd: Some(cwd.clone()),
+        })
+    };
     let fs = RealFs {};
     let http_client = RealHttpClient {};
-    let agent = Agent::recognize(&fs, &cwd)
-        .ok_or_else(|| eyre!("Couldn't find any lockfile inside {cwd:?} or any of its parents."))?;
+    let (mut settings, settings_error) =
+        match Settings::load(&fs, &cwd, user_config_dir().as_deref(), &|name| {
+            env::var(name).ok()
+        }) {
+            Ok(settings) => (settings, None),
+            Err(error) => (Settings::default(), Some(error)),
+        };
+    // completions don't depend on settings, which and doctor report broken settings by themselves
+    let settings_error = match settings_error {
+        Some(error)
+            if !matches!(
+                cli.command,
+                Some(
+                    Commands::Completions { .. }
+                        | Commands::Complete { .. }
+                        | Commands::Which { .. }
+                        | Commands::Doctor { .. }
+                )
+            ) =>
+        {
+            return Err(error)
+        }
+        error => error,
+    };
+    if cli.dry_run {
+        // commands that would be executed are the whole point of the dry run
+        settings.verbose = true;
+        // and nothing is written, shims included
+        settings.shims = false;
+    }
+    let bootstrap =
+        BootstrapExecutor::from_package_json(executor.as_ref(), &fs, &cwd, settings.bootstrap);
+    // nothing is run in the dry run so missing or mismatched package managers don't matter
+    let executor: &dyn Executor = if cli.dry_run {
+        executor.as_ref()
+    } else {
+        &bootstrap
+    };
+    let enforce_version = |agent| -> Result<()> {
+        if cli.dry_run {
+            return Ok(());
+        }
+        bootstrap.enforce_version(agent, settings.version_check)
+    };
+    // not every command needs to know the agent so it's resolved lazily
+    let agent_override = || {
+        cli.agent.or_else(|| {
+            env::var("NY_AGENT")
+                .ok()
+                .and_then(|value| Agent::from_env_var(&value))
+        })
+    };
+    let resolve_agent = || -> Result<Agent> {
+        if let Some(agent) = agent_override() {
+            return Ok(agent);
+        }
+        Agent::recognize(&fs, &cwd).or(settings.agent).ok_or_else(|| {
+            eyre!(
+                "Couldn't find any lockfile inside {cwd:?} or any of its parents.\n\
+                 Use --agent <npm|yarn|pnpm|bun> option, NY_AGENT env var or \"agent\" setting to choose package manager explicitly."
+            )
+        })
+    };
 
     match cli.command {
-        Some(Commands::Install) => install(&executor, &agent),
+        Some(Commands::Install) | None => {
+            let agent = resolve_agent()?;
+            enforce_version(agent)?;
+            install(executor, &agent, &settings)
+        }
         Some(Commands::Run { task, extra_args }) => {
             let task = task.as_str();
             let extra_args: Vec<&str> = extra_args.iter().map(String::as_str).collect();
-            run(&executor, &fs, &agent, task, &cwd, Some(&extra_args))
+            run(
+                executor,
+                &fs,
+                &resolve_agent()?,
+                task,
+                &cwd,
+                Some(&extra_args),
+                &settings,
+            )
         }
         Some(Commands::Add {
             packages,
             dev,
             workspace_root,
+            no_types,
         }) => {
-            add(&executor, &agent, dev, workspace_root, &packages, false)?;
-            if check_if_ts_repo(&fs, &cwd) {
+            let agent = resolve_agent()?;
+            enforce_version(agent)?;
+            add(
+                executor,
+                &agent,
+                dev,
+                workspace_root,
+                &packages,
+                false,
+                &settings,
+            )?;
+            if !no_types && settings.types.enabled && check_if_ts_repo(&fs, &cwd) {
                 install_ts_types(
-                    &executor,
+                    executor,
                     &fs,
                     &http_client,
                     &agent,
                     &cwd,
                     &packages,
                     workspace_root,
+                    &settings,
                 )
             } else {
                 Ok(())
             }
         }
-        None => install(&executor, &agent),
+        Some(Commands::Types {
+            command:
+                TypesCommands::Sync {
+                    prune,
+                    workspace_root,
+                },
+        }) => sync_ts_types(
+            executor,
+            &fs,
+            &http_client,
+            &resolve_agent()?,
+            &cwd,
+            workspace_root,
+            prune,
+            &settings,
+        ),
+        Some(Commands::Outdated { json }) => {
+            outdated(executor, &fs, &http_client, &resolve_agent()?, &cwd, json)
+        }
+        Some(Commands::Why { package }) => {
+            why(executor, &fs, &resolve_agent()?, &cwd, &package, &settings)
+        }
+        Some(Commands::Pkg { command }) => match command {
+            PkgCommands::Get { keys } => pkg_get(&fs, &cwd, &keys),
+            PkgCommands::Set { assignments, json } => {
+                pkg_set(&fs, &cwd, &assignments, json, cli.dry_run)
+            }
+            PkgCommands::Delete { keys } => pkg_delete(&fs, &cwd, &keys, cli.dry_run),
+        },
+        Some(Commands::Migrate { agent }) => {
+            migrate(executor, &fs, &cwd, &agent, &settings, cli.dry_run)
+        }
+        Some(Commands::LintScripts { fix }) => lint_scripts(&fs, &cwd, fix, cli.dry_run),
+        Some(Commands::Shim { program, args }) => shim(
+            executor,
+            &fs,
+            &resolve_agent()?,
+            &cwd,
+            &program,
+            &args,
+            &settings,
+            &env::var("PATH").unwrap_or_default(),
+        ),
+        Some(Commands::Which { json }) => which(
+            executor,
+            &fs,
+            &cwd,
+            agent_override(),
+            settings.agent,
+            settings_error.as_ref(),
+            json,
+        ),
+        Some(Commands::Doctor { offline }) => doctor(
+            executor,
+            &fs,
+            &http_client,
+            &cwd,
+            agent_override(),
+            settings.agent,
+            settings_error.as_ref(),
+            offline,
+        ),
+        Some(Commands::Completions { shell }) => completions(shell, &mut std::io::stdout()),
+        Some(Commands::Complete { prefix }) => complete(&fs, &cwd, &prefix),
     }?;
 
     Ok(())
 }
+
+/// Whether package.json closest to the directory (current one or passed with --cwd) has the script
+fn has_script(cwd: Option<&Path>, name: &str) -> bool {
+    let fs = RealFs {};
+    env::current_dir()
+        .ok()
+        .map(|dir| dir.join(cwd.unwrap_or(Path::new(""))))
+        .and_then(|dir| find_in_parents(&fs, &dir, "package.json"))
+        .and_then(|path| PackageManifest::load(&fs, &path).ok())
+        .is_some_and(|manifest| manifest.scripts.contains_key(name))
+}
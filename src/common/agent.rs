@@ -1,8 +1,13 @@
-use std::{collections::HashMap, path::Path};
+use colored::Colorize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use crate::fs::Filesystem;
+use crate::semver::Version;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
 pub enum Agent {
     Npm,
     Yarn,
@@ -12,22 +17,110 @@ pub enum Agent {
 
 impl Agent {
     pub fn recognize(fs: &dyn Filesystem, dir: &Path) -> Option<Self> {
-        if let Some(agent) = Self::recognize_in_dir(fs, dir) {
-            return Some(agent);
+        Self::recognize_lockfile(fs, dir).map(|(agent, _)| agent)
+    }
+
+    /// Same as `recognize` but also returns path of the lockfile that was used for recognition
+    pub fn recognize_lockfile(fs: &dyn Filesystem, dir: &Path) -> Option<(Self, PathBuf)> {
+        if let Some(found) = Self::find_lockfile_in_dir(fs, dir) {
+            return Some(found);
         }
         if let Some(parent) = dir.parent() {
-            return Self::recognize(fs, parent);
+            return Self::recognize_lockfile(fs, parent);
         }
         None
     }
 
-    fn recognize_in_dir(fs: &dyn Filesystem, dir: &Path) -> Option<Self> {
-        for (key, value) in FILE_TO_AGENT.iter() {
-            if fs.exists(dir.join(key).as_path()) {
-                return Some(*value);
-            }
+    /// All lockfiles in the directory and its parents, closest first
+    pub fn find_all_lockfiles(fs: &dyn Filesystem, dir: &Path) -> Vec<(Self, PathBuf)> {
+        dir.ancestors()
+            .flat_map(|dir| {
+                FILE_TO_AGENT
+                    .iter()
+                    .map(move |(lockfile, agent)| (*agent, dir.join(lockfile)))
+            })
+            .filter(|(_, path)| fs.exists(path))
+            .collect()
+    }
+
+    /// Name of the package manager's executable
+    pub fn program(&self) -> &'static str {
+        match self {
+            Agent::Npm => "npm",
+            Agent::Yarn => "yarn",
+            Agent::Pnpm => "pnpm",
+            Agent::Bun => "bun",
         }
-        None
+    }
+
+    pub(crate) fn recognize_in_dir(fs: &dyn Filesystem, dir: &Path) -> Option<Self> {
+        Self::find_lockfile_in_dir(fs, dir).map(|(agent, _)| agent)
+    }
+
+    /// How to get the package manager's binary when it's missing
+    pub fn install_hint(&self) -> &'static str {
+        match self {
+            Agent::Npm => "npm is bundled with Node.js, install it from https://nodejs.org",
+            Agent::Yarn => "run `corepack enable` or `npm install --global yarn`",
+            Agent::Pnpm => "run `corepack enable` or `npm install --global pnpm`",
+            Agent::Bun => "run `curl -fsSL https://bun.sh/install | bash`",
+        }
+    }
+
+    /// Flag making `install` fail instead of updating the lockfile, yarn 2+ renamed it to `--immutable`.
+    /// Unknown yarn version is treated as 2+, same as in `is_yarn_classic`
+    pub fn frozen_lockfile_flag(&self, version: Option<&Version>) -> &'static str {
+        match (self, version) {
+            (Agent::Yarn, Some(version)) if version.major == 1 => "--frozen-lockfile",
+            (Agent::Yarn, _) => "--immutable",
+            _ => "--frozen-lockfile",
+        }
+    }
+
+    pub fn from_program(program: &str) -> Option<Self> {
+        match program {
+            "npm" => Some(Agent::Npm),
+            "yarn" => Some(Agent::Yarn),
+            "pnpm" => Some(Agent::Pnpm),
+            "bun" => Some(Agent::Bun),
+            _ => None,
+        }
+    }
+
+    /// Parses `NY_AGENT` env var. It might be set globally so unlike `--agent` option an unknown value isn't an
+    /// error, it's ignored with a warning
+    pub fn from_env_var(value: &str) -> Option<Self> {
+        if value.is_empty() {
+            return None;
+        }
+        let agent = Self::from_program(&value.trim().to_lowercase());
+        if agent.is_none() {
+            // stderr keeps machine readable output (ex. `ny which --json`) intact
+            eprintln!(
+                "{}",
+                format!("Ignoring unknown NY_AGENT value: {value}, expected one of: npm, yarn, pnpm, bun")
+                    .yellow()
+            );
+        }
+
+        agent
+    }
+
+    /// Parses package.json's `packageManager` field ex. "pnpm@9.4.0+sha512.abc"
+    pub fn from_package_manager_field(field: &str) -> Option<(Self, Option<Version>)> {
+        let (program, version) = match field.split_once('@') {
+            Some((program, version)) => (program, Version::parse(version)),
+            None => (field, None),
+        };
+
+        Some((Self::from_program(program.trim())?, version))
+    }
+
+    fn find_lockfile_in_dir(fs: &dyn Filesystem, dir: &Path) -> Option<(Self, PathBuf)> {
+        FILE_TO_AGENT
+            .iter()
+            .map(|(lockfile, agent)| (*agent, dir.join(lockfile)))
+            .find(|(_, path)| fs.exists(path))
     }
 }
 
@@ -86,6 +179,53 @@ mod tests {
         assert_eq!(Agent::recognize(&mock_fs, dir), Some(Agent::Bun));
     }
 
+    #[test]
+    fn test_recognize_lockfile() {
+        let dir = Path::new("/project/nested");
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs
+            .expect_exists()
+            .returning(|path| path == Path::new("/project/pnpm-lock.yaml"));
+
+        assert_eq!(
+            Agent::recognize_lockfile(&mock_fs, dir),
+            Some((Agent::Pnpm, PathBuf::from("/project/pnpm-lock.yaml")))
+        );
+    }
+
+    #[test]
+    fn test_find_all_lockfiles() {
+        let dir = Path::new("/project/nested");
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|path| {
+            path == Path::new("/project/nested/bun.lock")
+                || path == Path::new("/project/package-lock.json")
+                || path == Path::new("/yarn.lock")
+        });
+
+        assert_eq!(
+            Agent::find_all_lockfiles(&mock_fs, dir),
+            vec![
+                (Agent::Bun, PathBuf::from("/project/nested/bun.lock")),
+                (Agent::Npm, PathBuf::from("/project/package-lock.json")),
+                (Agent::Yarn, PathBuf::from("/yarn.lock")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_package_manager_field() {
+        assert_eq!(
+            Agent::from_package_manager_field("pnpm@9.4.0+sha512.abc"),
+            Some((Agent::Pnpm, Version::parse("9.4.0")))
+        );
+        assert_eq!(
+            Agent::from_package_manager_field("yarn"),
+            Some((Agent::Yarn, None))
+        );
+        assert_eq!(Agent::from_package_manager_field("deno@1.0.0"), None);
+    }
+
     #[test]
     fn test_not_recognized() {
         let dir = Path::new("/any-project");
@@ -97,4 +237,12 @@ mod tests {
 
         assert_eq!(Agent::recognize(&mock_fs, dir), None);
     }
+
+    #[test]
+    fn test_from_env_var() {
+        assert_eq!(Agent::from_env_var("pnpm"), Some(Agent::Pnpm));
+        assert_eq!(Agent::from_env_var("Yarn"), Some(Agent::Yarn));
+        assert_eq!(Agent::from_env_var("deno"), None);
+        assert_eq!(Agent::from_env_var(""), None);
+    }
 }
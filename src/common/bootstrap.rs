@@ -0,0 +1,463 @@
+use colored::Colorize;
+use eyre::{eyre, Result};
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+
+use crate::agent::Agent;
+use crate::config::VersionCheck;
+use crate::execute::Executor;
+use crate::fs::{find_in_parents, Filesystem};
+use crate::manifest::PackageManifest;
+use crate::semver::Version;
+
+/// Runs package managers that are missing in PATH (when `bootstrap` setting is on) or have wrong version (see
+/// `enforce_version`) through corepack (yarn and pnpm) or npx. Both are opt-in as they download the package
+/// manager, npx asks before doing so.
+/// Version declared in package.json's `packageManager` field is used when it matches the agent.
+/// Corepack doesn't need to be enabled for this, `corepack pnpm@<version>` works out of the box.
+pub struct BootstrapExecutor<'a> {
+    inner: &'a dyn Executor,
+    package_manager: Option<(Agent, Option<Version>)>,
+    /// run agents missing in PATH through corepack or npx instead of failing
+    run_missing: bool,
+    /// run agents through corepack or npx even when they are installed, set by `enforce_version`
+    force_launcher: Cell<bool>,
+}
+
+impl<'a> BootstrapExecutor<'a> {
+    pub fn new(
+        inner: &'a dyn Executor,
+        package_manager: Option<(Agent, Option<Version>)>,
+        run_missing: bool,
+    ) -> Self {
+        Self {
+            inner,
+            package_manager,
+            run_missing,
+            force_launcher: Cell::new(false),
+        }
+    }
+
+    /// Uses `packageManager` field from the closest package.json
+    pub fn from_package_json(
+        inner: &'a dyn Executor,
+        fs: &dyn Filesystem,
+        cwd: &Path,
+        run_missing: bool,
+    ) -> Self {
+        Self::new(
+            inner,
+            find_package_manager_field(fs, cwd)
+                .and_then(|field| Agent::from_package_manager_field(&field)),
+            run_missing,
+        )
+    }
+
+    /// Compares installed agent's version with the one declared in `packageManager` field
+    pub fn enforce_version(&self, agent: Agent, version_check: VersionCheck) -> Result<()> {
+        let declared_version = match &self.package_manager {
+            Some((declared_agent, Some(version))) if *declared_agent == agent => version,
+            _ => return Ok(()),
+        };
+        let program = agent.program();
+        if version_check == VersionCheck::Off || self.inner.which(program).is_none() {
+            // missing agent is run with the declared version anyway
+            return Ok(());
+        }
+        let Some(installed_version) = self
+            .inner
+            .output(program, &["--version"])
+            .ok()
+            .and_then(|output| Version::parse(output.trim()))
+        else {
+            return Ok(());
+        };
+        if &installed_version == declared_version {
+            return Ok(());
+        }
+
+        let message = format!(
+            "packageManager field declares {program}@{declared_version} but {program} {installed_version} is installed"
+        );
+        match version_check {
+            VersionCheck::Off => Ok(()),
+            VersionCheck::Warn => {
+                println!(
+                    "{}",
+                    format!(
+                        "{message}. Set version_check = \"corepack\" to use the declared version"
+                    )
+                    .yellow()
+                );
+                Ok(())
+            }
+            VersionCheck::Error => Err(eyre!(
+                "{message}. Install {program}@{declared_version} or set version_check = \"corepack\""
+            )),
+            VersionCheck::Corepack => {
+                let (launcher, _) = self.launcher(agent).ok_or_else(|| {
+                    eyre!("{message} and neither corepack nor npx is available to run the declared version")
+                })?;
+                println!(
+                    "{}",
+                    format!("{message}, running the declared version with {launcher}").yellow()
+                );
+                self.force_launcher.set(true);
+                Ok(())
+            }
+        }
+    }
+
+    /// Program and args to prepend for running the agent without its binary
+    fn launcher(&self, agent: Agent) -> Option<(&'static str, Vec<String>)> {
+        let version = match &self.package_manager {
+            Some((declared_agent, version)) if *declared_agent == agent => version.as_ref(),
+            _ => None,
+        };
+        let program = agent.program();
+
+        if matches!(agent, Agent::Yarn | Agent::Pnpm) && self.inner.which("corepack").is_some() {
+            let spec = match version {
+                Some(version) => format!("{program}@{version}"),
+                None => program.to_string(),
+            };
+            return Some(("corepack", vec![spec]));
+        }
+        if self.inner.which("npx").is_some() {
+            // yarn 2+ isn't published as `yarn` package
+            let package = match version {
+                Some(version) if agent == Agent::Yarn && version.major >= 2 => {
+                    format!("@yarnpkg/cli-dist@{version}")
+                }
+                Some(version) => format!("{program}@{version}"),
+                None => program.to_string(),
+            };
+            // no `--yes`, npx asks before downloading the package
+            return Some((
+                "npx",
+                vec!["--package".to_string(), package, program.to_string()],
+            ));
+        }
+
+        None
+    }
+
+    fn execute_with_launcher(
+        &self,
+        launcher: &str,
+        launcher_args: Vec<String>,
+        args: &[&str],
+        extra_path: Option<String>,
+        verbose: bool,
+        silence_stdout: bool,
+    ) -> Result<()> {
+        let mut all_args: Vec<&str> = launcher_args.iter().map(String::as_str).collect();
+        all_args.extend_from_slice(args);
+        self.inner
+            .execute(launcher, &all_args, extra_path, verbose, silence_stdout)
+    }
+}
+
+impl Executor for BootstrapExecutor<'_> {
+    fn execute(
+        &self,
+        program: &str,
+        args: &[&str],
+        extra_path: Option<String>,
+        verbose: bool,
+        silence_stdout: bool,
+    ) -> Result<()> {
+        let agent = Agent::from_program(program);
+        let missing_agent = agent.filter(|_| self.inner.which(program).is_none());
+        if let (Some(agent), true) = (agent, self.force_launcher.get()) {
+            let (launcher, launcher_args) = self
+                .launcher(agent)
+                .ok_or_else(|| eyre!("Couldn't find corepack or npx in PATH"))?;
+            return self.execute_with_launcher(
+                launcher,
+                launcher_args,
+                args,
+                extra_path,
+                verbose,
+                silence_stdout,
+            );
+        }
+        let Some(agent) = missing_agent else {
+            return self
+                .inner
+                .execute(program, args, extra_path, verbose, silence_stdout);
+        };
+
+        let not_found = || {
+            eyre!(
+                "Couldn't find {program} in PATH. To install it {}",
+                agent.install_hint()
+            )
+        };
+        if !self.run_missing {
+            return Err(not_found());
+        }
+        let (launcher, launcher_args) = self.launcher(agent).ok_or_else(not_found)?;
+        println!(
+            "{}",
+            format!(
+                "{program} is not installed, running it with {launcher}. To install it {}",
+                agent.install_hint()
+            )
+            .yellow()
+        );
+
+        self.execute_with_launcher(
+            launcher,
+            launcher_args,
+            args,
+            extra_path,
+            verbose,
+            silence_stdout,
+        )
+    }
+
+    fn output(&self, program: &str, args: &[&str]) -> Result<String> {
+        self.inner.output(program, args)
+    }
+
+    fn which(&self, program: &str) -> Option<PathBuf> {
+        self.inner.which(program)
+    }
+}
+
+/// Value of `packageManager` field from the closest package.json
+pub fn find_package_manager_field(fs: &dyn Filesystem, cwd: &Path) -> Option<String> {
+    find_in_parents(fs, cwd, "package.json")
+        .and_then(|path| PackageManifest::load(fs, &path).ok())
+        .and_then(|manifest| manifest.package_manager)
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use crate::execute::{expect_execute_once, MockExecutor};
+    use crate::utils::vec_of_strings;
+
+    use super::*;
+
+    fn expect_in_path(mock_executor: &mut MockExecutor, programs: &'static [&'static str]) {
+        mock_executor.expect_which().returning(move |program| {
+            programs
+                .contains(&program)
+                .then(|| PathBuf::from("/usr/bin").join(program))
+        });
+    }
+
+    #[test]
+    fn test_execute_installed_agent() {
+        let mut mock_executor = MockExecutor::new();
+        expect_in_path(&mut mock_executor, &["pnpm"]);
+        expect_execute_once(
+            &mut mock_executor,
+            "pnpm",
+            vec_of_strings!["install"],
+            None,
+            true,
+            false,
+        );
+        let executor = BootstrapExecutor::new(&mock_executor, None, false);
+
+        assert!(executor
+            .execute("pnpm", &["install"], None, true, false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_execute_through_corepack() {
+        let mut mock_executor = MockExecutor::new();
+        expect_in_path(&mut mock_executor, &["corepack", "npx"]);
+        expect_execute_once(
+            &mut mock_executor,
+            "corepack",
+            vec_of_strings!["pnpm@9.4.0", "add", "zod"],
+            None,
+            true,
+            false,
+        );
+        let executor = BootstrapExecutor::new(
+            &mock_executor,
+            Some((Agent::Pnpm, Version::parse("9.4.0"))),
+            true,
+        );
+
+        assert!(executor
+            .execute("pnpm", &["add", "zod"], None, true, false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_execute_through_npx() {
+        let mut mock_executor = MockExecutor::new();
+        expect_in_path(&mut mock_executor, &["npx"]);
+        expect_execute_once(
+            &mut mock_executor,
+            "npx",
+            vec_of_strings!["--package", "@yarnpkg/cli-dist@4.1.0", "yarn", "install"],
+            None,
+            true,
+            false,
+        );
+        let executor = BootstrapExecutor::new(
+            &mock_executor,
+            Some((Agent::Yarn, Version::parse("4.1.0"))),
+            true,
+        );
+
+        assert!(executor
+            .execute("yarn", &["install"], None, true, false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_execute_ignores_version_of_other_agent() {
+        let mut mock_executor = MockExecutor::new();
+        expect_in_path(&mut mock_executor, &["npx"]);
+        expect_execute_once(
+            &mut mock_executor,
+            "npx",
+            vec_of_strings!["--package", "bun", "bun", "install"],
+            None,
+            true,
+            false,
+        );
+        let executor = BootstrapExecutor::new(
+            &mock_executor,
+            Some((Agent::Pnpm, Version::parse("9.4.0"))),
+            true,
+        );
+
+        assert!(executor
+            .execute("bun", &["install"], None, true, false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_execute_without_fallback() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor.expect_which().returning(|_| None);
+        mock_executor.expect_execute().never();
+        let executor = BootstrapExecutor::new(&mock_executor, None, false);
+
+        let error = executor
+            .execute("npm", &["install"], None, true, false)
+            .unwrap_err();
+        assert!(error.to_string().contains("nodejs.org"));
+    }
+
+    #[test]
+    fn test_execute_missing_agent_without_opt_in() {
+        let mut mock_executor = MockExecutor::new();
+        expect_in_path(&mut mock_executor, &["corepack", "npx"]);
+        mock_executor.expect_execute().never();
+        let executor = BootstrapExecutor::new(
+            &mock_executor,
+            Some((Agent::Pnpm, Version::parse("9.4.0"))),
+            false,
+        );
+
+        let error = executor
+            .execute("pnpm", &["install"], None, true, false)
+            .unwrap_err();
+        assert!(error.to_string().contains("corepack enable"));
+    }
+
+    #[test]
+    fn test_execute_other_program() {
+        let mut mock_executor = MockExecutor::new();
+        mock_executor.expect_which().with(eq("sh")).never();
+        expect_execute_once(
+            &mut mock_executor,
+            "sh",
+            vec_of_strings!["-c", "true"],
+            None,
+            false,
+            false,
+        );
+        let executor = BootstrapExecutor::new(&mock_executor, None, false);
+
+        assert!(executor
+            .execute("sh", &["-c", "true"], None, false, false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_enforce_version_matching() {
+        let mut mock_executor = MockExecutor::new();
+        expect_in_path(&mut mock_executor, &["yarn", "corepack"]);
+        expect_version(&mut mock_executor, "yarn", "4.1.0\n");
+        let executor = BootstrapExecutor::new(
+            &mock_executor,
+            Some((Agent::Yarn, Version::parse("4.1.0"))),
+            false,
+        );
+
+        assert!(executor
+            .enforce_version(Agent::Yarn, VersionCheck::Error)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_enforce_version_mismatch() {
+        let mut mock_executor = MockExecutor::new();
+        expect_in_path(&mut mock_executor, &["yarn", "corepack"]);
+        expect_version(&mut mock_executor, "yarn", "1.22.19\n");
+        let executor = BootstrapExecutor::new(
+            &mock_executor,
+            Some((Agent::Yarn, Version::parse("4.1.0"))),
+            false,
+        );
+
+        assert!(executor
+            .enforce_version(Agent::Yarn, VersionCheck::Warn)
+            .is_ok());
+        let error = executor
+            .enforce_version(Agent::Yarn, VersionCheck::Error)
+            .unwrap_err();
+        assert!(error.to_string().contains("yarn 1.22.19 is installed"));
+    }
+
+    #[test]
+    fn test_enforce_version_through_corepack() {
+        let mut mock_executor = MockExecutor::new();
+        expect_in_path(&mut mock_executor, &["pnpm", "corepack"]);
+        expect_version(&mut mock_executor, "pnpm", "8.15.0\n");
+        expect_execute_once(
+            &mut mock_executor,
+            "corepack",
+            vec_of_strings!["pnpm@9.4.0", "install"],
+            None,
+            true,
+            false,
+        );
+        let executor = BootstrapExecutor::new(
+            &mock_executor,
+            Some((Agent::Pnpm, Version::parse("9.4.0"))),
+            false,
+        );
+
+        executor
+            .enforce_version(Agent::Pnpm, VersionCheck::Corepack)
+            .unwrap();
+        assert!(executor
+            .execute("pnpm", &["install"], None, true, false)
+            .is_ok());
+    }
+
+    fn expect_version(
+        mock_executor: &mut MockExecutor,
+        program: &'static str,
+        version: &'static str,
+    ) {
+        mock_executor
+            .expect_output()
+            .withf(move |_program, args| _program == program && args == ["--version"])
+            .returning(move |_, _| Ok(version.to_string()));
+    }
+}
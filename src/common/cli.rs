@@ -1,10 +1,25 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::path::{Path, PathBuf};
+
+use crate::{agent::Agent, commands::completions::CompletionShell};
 
 #[derive(Parser, PartialEq, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Print commands that would be executed without running them
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// Run as if ny was started in <DIR> instead of the current working directory
+    #[arg(short = 'C', long, value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Package manager to use instead of detecting it from the lockfile. Can be set with NY_AGENT env var as well
+    #[arg(long, value_enum)]
+    pub agent: Option<Agent>,
 }
 
 // @note: we do pre-parsing of CLI args in `parse_from` function down below
@@ -43,27 +58,211 @@ pub enum Commands {
         /// Add root workspace dependency
         #[arg(short, short_alias = 'W', long, default_value_t = false)]
         workspace_root: bool,
+        /// Don't install missing @types packages
+        #[arg(long, default_value_t = false)]
+        no_types: bool,
+    },
+
+    /// Explain which package manager is used and why
+    #[command(alias("agent"))]
+    Which {
+        /// Print output as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+
+    /// Check if the environment is set up correctly for the project
+    Doctor {
+        /// Skip checks requiring network access
+        #[arg(long, default_value_t = false)]
+        offline: bool,
+    },
+
+    /// Generate shell completions script, ex. `source <(ny completions bash)`
+    Completions {
+        #[arg(value_enum)]
+        shell: CompletionShell,
+    },
+
+    /// List tasks starting with the prefix, used by shell completions
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        #[arg(default_value = "", allow_hyphen_values = true)]
+        prefix: String,
+    },
+
+    /// Handle package manager call intercepted by a shim, see `shims` setting
+    #[command(name = "__shim", hide = true)]
+    Shim {
+        program: String,
+
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Manage TypeScript @types packages
+    Types {
+        #[command(subcommand)]
+        command: TypesCommands,
+    },
+
+    /// List dependencies with newer versions available
+    Outdated {
+        /// Print output as JSON
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+
+    /// Explain why a package is installed
+    Why {
+        /// Name of the package
+        package: String,
+    },
+
+    /// Read and edit fields of the closest package.json
+    Pkg {
+        #[command(subcommand)]
+        command: PkgCommands,
+    },
+
+    /// Switch the project to another package manager
+    Migrate {
+        /// Package manager to switch to
+        #[arg(value_enum)]
+        agent: Agent,
+    },
+
+    /// Find scripts calling package managers directly (ex. `yarn build`) which could use ny instead
+    LintScripts {
+        /// Rewrite found calls in package.json
+        #[arg(long, default_value_t = false)]
+        fix: bool,
+    },
+}
+
+#[derive(Subcommand, PartialEq, Debug)]
+pub enum TypesCommands {
+    /// Install missing @types for all dependencies and find @types of removed dependencies
+    Sync {
+        /// Remove @types packages of libraries that are no longer dependencies
+        #[arg(long, default_value_t = false)]
+        prune: bool,
+        /// Add root workspace dependency
+        #[arg(short, short_alias = 'W', long, default_value_t = false)]
+        workspace_root: bool,
+    },
+}
+
+#[derive(Subcommand, PartialEq, Debug)]
+pub enum PkgCommands {
+    /// Print values of fields, ex. `ny pkg get scripts.build`. Prints whole package.json without keys
+    Get {
+        /// Dotted paths of fields
+        keys: Vec<String>,
+    },
+    /// Set fields, ex. `ny pkg set engines.node=">=18"`
+    Set {
+        /// Assignments in key=value format
+        #[arg(required = true)]
+        assignments: Vec<String>,
+        /// Parse values as JSON, ex. `ny pkg set private=true --json`
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Remove fields
+    #[command(alias("rm"))]
+    Delete {
+        /// Dotted paths of fields
+        #[arg(required = true)]
+        keys: Vec<String>,
     },
 }
 
+// top level options that need to be skipped to find the command. Must be passed before the command
+const GLOBAL_OPTIONS_WITH_VALUE: &[&str] = &["--cwd", "-C", "--agent"];
+
+// names (and aliases) of all commands, anything else is treated as a task name
+const COMMANDS: &[&str] = &[
+    "install",
+    "i",
+    "run",
+    "r",
+    "add",
+    "a",
+    "types",
+    "pkg",
+    "outdated",
+    "why",
+    "migrate",
+    "lint-scripts",
+    "which",
+    "agent",
+    "doctor",
+    "completions",
+    "__complete",
+    "__shim",
+];
+
+// commands added after `ny <task>` shortcut, scripts with the same names keep precedence over them
+const SCRIPT_NAMED_COMMANDS: &[&str] = &[
+    "types",
+    "pkg",
+    "outdated",
+    "why",
+    "migrate",
+    "lint-scripts",
+    "which",
+    "agent",
+    "doctor",
+    "completions",
+];
+
+/// Whether the name is one of ny commands (or their aliases) rather than a task
+pub fn is_command(name: &str) -> bool {
+    COMMANDS.contains(&name)
+}
+
 // our cli is too complex to parse by clap alone so first we do a little bit of preprocessing
-pub fn parse_from(mut args: Vec<String>) -> Cli {
-    if args.len() > 1 {
+pub fn parse_from(args: Vec<String>) -> Cli {
+    parse_with_scripts(args, |_, _| false)
+}
+
+/// Same as `parse_from` but commands named like a script of the project (`has_script` gets `--cwd` value and
+/// the name) run the script instead, unless they're followed by their subcommand ex. `ny types sync`
+pub fn parse_with_scripts(
+    mut args: Vec<String>,
+    has_script: impl Fn(Option<&Path>, &str) -> bool,
+) -> Cli {
+    let command_index = find_command_index(&args);
+
+    if args.len() > command_index {
+        if is_shadowed_by_script(&args, command_index, has_script) {
+            args.insert(command_index, "run".to_string());
+        }
+
         // if first arg is a task name
-        if (args[1] != "install" && args[1] != "i")
-            && (args[1] != "run" && args[1] != "r")
-            && (args[1] != "add" && args[1] != "a")
+        if !COMMANDS.contains(&args[command_index].as_str())
             // and is not a flag
-            && !args[1].starts_with('-')
+            && !args[command_index].starts_with('-')
         {
             // append "run" arg as the default
-            args.insert(1, "run".to_string());
+            args.insert(command_index, "run".to_string());
         }
 
-        if args[1] == "run" || args[1] == "r" {
-            // if there are only 4 args in total and --help at the end
+        // "types" is also a common script name (ex. `tsc --noEmit`) so without a subcommand it's a task
+        if args[command_index] == "types"
+            && !args.get(command_index + 1).is_some_and(|arg| {
+                TypesCommands::has_subcommand(arg)
+                    || ["help", "-h", "--help"].contains(&arg.as_str())
+            })
+        {
+            args.insert(command_index, "run".to_string());
+        }
+
+        if args[command_index] == "run" || args[command_index] == "r" {
+            // if there are only 2 args after "run" and --help at the end
             // ex. "ny run program --help"
-            if args.len() == 4 && args.last() == Some(&"--help".to_string()) {
+            if args.len() == command_index + 3 && args.last() == Some(&"--help".to_string()) {
                 // prepend it with -- so clap doesnt confuse it with --help flag for ny itself
                 args.insert(args.len() - 1, "--".to_string());
             }
@@ -72,6 +271,53 @@ pub fn parse_from(mut args: Vec<String>) -> Cli {
     Cli::parse_from(args)
 }
 
+fn is_shadowed_by_script(
+    args: &[String],
+    command_index: usize,
+    has_script: impl Fn(Option<&Path>, &str) -> bool,
+) -> bool {
+    let name = args[command_index].as_str();
+    if !SCRIPT_NAMED_COMMANDS.contains(&name) || !has_script(find_cwd_arg(args), name) {
+        return false;
+    }
+
+    let cli = Cli::command();
+    let has_subcommand = |arg: &String| {
+        cli.find_subcommand(name)
+            .is_some_and(|command| command.find_subcommand(arg).is_some())
+    };
+    !args.get(command_index + 1).is_some_and(has_subcommand)
+}
+
+// value of --cwd/-C passed before the command
+fn find_cwd_arg(args: &[String]) -> Option<&Path> {
+    let command_index = find_command_index(args);
+    let options = &args[..command_index.min(args.len())];
+    options
+        .iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.as_str() {
+            "--cwd" | "-C" => options.get(i + 1).map(Path::new),
+            arg => arg.strip_prefix("--cwd=").map(Path::new),
+        })
+}
+
+// skips over top level options (ex. "ny --dry-run add pkg") to find where the command starts
+fn find_command_index(args: &[String]) -> usize {
+    let mut index = 1;
+    while let Some(arg) = args.get(index) {
+        if GLOBAL_OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+            index += 2;
+        } else if arg.starts_with('-') {
+            index += 1;
+        } else {
+            break;
+        }
+    }
+
+    index
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,6 +329,16 @@ mod tests {
         Cli::command().debug_assert();
     }
 
+    #[test]
+    fn verify_commands_list() {
+        use clap::CommandFactory;
+        for command in Cli::command().get_subcommands() {
+            for name in std::iter::once(command.get_name()).chain(command.get_all_aliases()) {
+                assert!(COMMANDS.contains(&name), "{name} is missing in COMMANDS");
+            }
+        }
+    }
+
     #[test]
     fn run_command_extra_args() {
         let parsed = parse_from(vec_of_strings!["/ny", "mocha", "--arg1", "--arg2", "abc"]);
@@ -157,7 +413,8 @@ mod tests {
             Some(Commands::Add {
                 packages: vec_of_strings!["pkg"],
                 dev: true,
-                workspace_root: false
+                workspace_root: false,
+                no_types: false
             })
         );
     }
@@ -171,7 +428,178 @@ mod tests {
             Some(Commands::Add {
                 packages: vec_of_strings!["pkg"],
                 dev: true,
-                workspace_root: false
+                workspace_root: false,
+                no_types: false
+            })
+        );
+    }
+
+    #[test]
+    fn types_sync_prune() {
+        let parsed = parse_from(vec_of_strings!["/ny", "types", "sync", "--prune"]);
+
+        assert_eq!(
+            parsed.command,
+            Some(Commands::Types {
+                command: TypesCommands::Sync {
+                    prune: true,
+                    workspace_root: false
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn types_without_subcommand_runs_task() {
+        let parsed = parse_from(vec_of_strings!["/ny", "types", "--watch"]);
+
+        assert_eq!(
+            parsed.command,
+            Some(Commands::Run {
+                task: "types".to_string(),
+                extra_args: vec_of_strings!["--watch"]
+            })
+        );
+    }
+
+    #[test]
+    fn add_package_no_types() {
+        let parsed = parse_from(vec_of_strings!["/ny", "add", "pkg", "--no-types"]);
+
+        assert_eq!(
+            parsed.command,
+            Some(Commands::Add {
+                packages: vec_of_strings!["pkg"],
+                dev: false,
+                workspace_root: false,
+                no_types: true
+            })
+        );
+    }
+
+    #[test]
+    fn dry_run_add() {
+        let parsed = parse_from(vec_of_strings!["/ny", "--dry-run", "add", "-D", "zod"]);
+
+        assert!(parsed.dry_run);
+        assert_eq!(
+            parsed.command,
+            Some(Commands::Add {
+                packages: vec_of_strings!["zod"],
+                dev: true,
+                workspace_root: false,
+                no_types: false
+            })
+        );
+    }
+
+    #[test]
+    fn dry_run_naked_command_help_arg() {
+        let parsed = parse_from(vec_of_strings!["/ny", "--dry-run", "mocha", "--help"]);
+
+        assert!(parsed.dry_run);
+        assert_eq!(
+            parsed.command,
+            Some(Commands::Run {
+                task: "mocha".to_string(),
+                extra_args: vec_of_strings!["--help"]
+            })
+        );
+    }
+
+    #[test]
+    fn cwd_run_task() {
+        let parsed = parse_from(vec_of_strings![
+            "/ny",
+            "-C",
+            "packages/app",
+            "test",
+            "--bail"
+        ]);
+
+        assert_eq!(parsed.cwd, Some(PathBuf::from("packages/app")));
+        assert_eq!(
+            parsed.command,
+            Some(Commands::Run {
+                task: "test".to_string(),
+                extra_args: vec_of_strings!["--bail"]
+            })
+        );
+    }
+
+    #[test]
+    fn cwd_long_with_equals_sign() {
+        let parsed = parse_from(vec_of_strings!["/ny", "--cwd=packages/app", "add", "pkg"]);
+
+        assert_eq!(parsed.cwd, Some(PathBuf::from("packages/app")));
+        assert!(matches!(parsed.command, Some(Commands::Add { .. })));
+    }
+
+    #[test]
+    fn agent_override() {
+        let parsed = parse_from(vec_of_strings!["/ny", "--agent", "pnpm", "add", "pkg"]);
+
+        assert_eq!(parsed.agent, Some(Agent::Pnpm));
+        assert!(matches!(parsed.command, Some(Commands::Add { .. })));
+    }
+
+    #[test]
+    fn which_alias() {
+        let parsed = parse_from(vec_of_strings!["/ny", "agent", "--json"]);
+
+        assert_eq!(parsed.command, Some(Commands::Which { json: true }));
+    }
+
+    #[test]
+    fn script_named_like_command_runs() {
+        let has_script = |cwd: Option<&Path>, name: &str| {
+            cwd == Some(Path::new("app")) && ["outdated", "types"].contains(&name)
+        };
+
+        assert_eq!(
+            parse_with_scripts(vec_of_strings!["/ny", "-C", "app", "outdated"], has_script).command,
+            Some(Commands::Run {
+                task: "outdated".to_string(),
+                extra_args: vec![]
+            })
+        );
+        assert!(matches!(
+            parse_with_scripts(
+                vec_of_strings!["/ny", "--cwd=app", "types", "sync"],
+                has_script
+            )
+            .command,
+            Some(Commands::Types { .. })
+        ));
+        assert_eq!(
+            parse_with_scripts(vec_of_strings!["/ny", "outdated"], has_script).command,
+            Some(Commands::Outdated { json: false })
+        );
+    }
+
+    #[test]
+    fn complete_prefix() {
+        let parsed = parse_from(vec_of_strings!["/ny", "__complete", "te"]);
+
+        assert_eq!(
+            parsed.command,
+            Some(Commands::Complete {
+                prefix: "te".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn shim_passes_all_args() {
+        let parsed = parse_from(vec_of_strings![
+            "/ny", "__shim", "npm", "run", "build", "--watch"
+        ]);
+
+        assert_eq!(
+            parsed.command,
+            Some(Commands::Shim {
+                program: "npm".to_string(),
+                args: vec_of_strings!["run", "build", "--watch"]
             })
         );
     }
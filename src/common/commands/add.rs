@@ -1,4 +1,4 @@
-use crate::{agent::Agent, execute::Executor};
+use crate::{agent::Agent, config::Settings, execute::Executor};
 use eyre::{eyre, Result};
 
 pub fn add(
@@ -8,6 +8,7 @@ pub fn add(
     workspace_root: bool,
     packages: &[String],
     silence_stdout: bool,
+    settings: &Settings,
 ) -> Result<()> {
     let packages_refs: Vec<_> = packages.iter().map(|s| s.as_str()).collect();
 
@@ -40,7 +41,7 @@ pub fn add(
                 packages_refs,
             ),
             None,
-            true,
+            settings.verbose,
             silence_stdout,
         ),
         Agent::Yarn => executor.execute(
@@ -52,7 +53,7 @@ pub fn add(
                 packages_refs,
             ),
             None,
-            true,
+            settings.verbose,
             silence_stdout,
         ),
         Agent::Pnpm => executor.execute(
@@ -64,7 +65,7 @@ pub fn add(
                 packages_refs,
             ),
             None,
-            true,
+            settings.verbose,
             silence_stdout,
         ),
         Agent::Bun => {
@@ -81,7 +82,7 @@ pub fn add(
                     packages_refs,
                 ),
                 None,
-                true,
+                settings.verbose,
                 silence_stdout,
             )
         }
@@ -112,8 +113,9 @@ mod tests {
             &Agent::Npm,
             false,
             false,
-            &vec_of_strings!["packageA", "packageB"],
+            &["packageA", "packageB"].map(String::from),
             false,
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
@@ -136,8 +138,9 @@ mod tests {
             &Agent::Npm,
             false,
             true,
-            &vec_of_strings!["packageA", "packageB"],
+            &["packageA", "packageB"].map(String::from),
             false,
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
@@ -160,8 +163,9 @@ mod tests {
             &Agent::Yarn,
             true,
             false,
-            &vec_of_strings!["packageA", "packageB"],
+            &["packageA", "packageB"].map(String::from),
             false,
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
@@ -184,8 +188,9 @@ mod tests {
             &Agent::Yarn,
             true,
             true,
-            &vec_of_strings!["packageA", "packageB"],
+            &["packageA", "packageB"].map(String::from),
             false,
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
@@ -208,8 +213,9 @@ mod tests {
             &Agent::Pnpm,
             true,
             false,
-            &vec_of_strings!["packageA", "packageB"],
+            &["packageA", "packageB"].map(String::from),
             false,
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
@@ -232,8 +238,9 @@ mod tests {
             &Agent::Pnpm,
             true,
             true,
-            &vec_of_strings!["packageA", "packageB"],
+            &["packageA", "packageB"].map(String::from),
             false,
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
@@ -256,8 +263,9 @@ mod tests {
             &Agent::Bun,
             true,
             false,
-            &vec_of_strings!["packageA", "packageB"],
+            &["packageA", "packageB"].map(String::from),
             false,
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
@@ -0,0 +1,155 @@
+use clap::{CommandFactory, ValueEnum};
+use eyre::Result;
+use std::io::Write;
+use std::path::Path;
+
+use crate::{
+    cli::Cli,
+    fs::{find_in_parents, Filesystem},
+    manifest::PackageManifest,
+};
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Debug)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+// static completions generated by clap know nothing about tasks so every script gets a little addition
+// asking `ny __complete <prefix>` for tasks when completing the first argument (or the one after `run`)
+const BASH_TASKS: &str = r#"
+_ny_tasks() {
+    _ny "$@"
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    if [[ ${COMP_CWORD} -eq 1 || ( ${COMP_CWORD} -eq 2 && ( "${prev}" == "run" || "${prev}" == "r" ) ) ]]; then
+        COMPREPLY+=( $(compgen -W "$(ny __complete "${cur}" 2>/dev/null)" -- "${cur}") )
+    fi
+}
+complete -F _ny_tasks -o bashdefault -o default ny
+"#;
+
+const ZSH_TASKS: &str = r#"
+_ny_tasks() {
+    _ny "$@"
+    if (( CURRENT == 2 )) || [[ ${CURRENT} -eq 3 && ( ${words[2]} == run || ${words[2]} == r ) ]]; then
+        local -a tasks
+        tasks=(${(f)"$(ny __complete "${PREFIX}" 2>/dev/null)"})
+        compadd -a tasks
+    fi
+}
+compdef _ny_tasks ny
+"#;
+
+const FISH_TASKS: &str = r#"
+complete -c ny -n "__fish_use_subcommand" -f -a "(ny __complete (commandline -ct) 2>/dev/null)"
+complete -c ny -n "__fish_seen_subcommand_from run r; and test (count (commandline -opc)) -eq 2" -f -a "(ny __complete (commandline -ct) 2>/dev/null)"
+"#;
+
+pub fn completions(shell: CompletionShell, out: &mut dyn Write) -> Result<()> {
+    let (generator, tasks_script) = match shell {
+        CompletionShell::Bash => (clap_complete::Shell::Bash, BASH_TASKS),
+        CompletionShell::Zsh => (clap_complete::Shell::Zsh, ZSH_TASKS),
+        CompletionShell::Fish => (clap_complete::Shell::Fish, FISH_TASKS),
+    };
+
+    clap_complete::generate(generator, &mut Cli::command(), "ny", out);
+    out.write_all(tasks_script.as_bytes())?;
+
+    Ok(())
+}
+
+/// Prints out tasks starting with the prefix, one per line. Used by shell completions
+pub fn complete(fs: &dyn Filesystem, cwd: &Path, prefix: &str) -> Result<()> {
+    for task in list_tasks(fs, cwd) {
+        if task.starts_with(prefix) {
+            println!("{task}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Scripts from package.json followed by binaries from node_modules/.bin directories that `ny run` would find
+pub fn list_tasks(fs: &dyn Filesystem, cwd: &Path) -> Vec<String> {
+    let package_json_path = find_in_parents(fs, cwd, "package.json");
+    let mut tasks: Vec<String> = package_json_path
+        .as_ref()
+        .and_then(|path| PackageManifest::load(fs, path).ok())
+        .map(|manifest| manifest.scripts.into_keys().collect())
+        .unwrap_or_default();
+
+    let root = package_json_path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .unwrap_or(cwd);
+    let mut binaries = Vec::new();
+    for dir in root.ancestors() {
+        if let Ok(names) = fs.read_dir(&dir.join("node_modules/.bin")) {
+            binaries.extend(names);
+        }
+    }
+    binaries.sort();
+
+    for binary in binaries {
+        if !tasks.contains(&binary) {
+            tasks.push(binary);
+        }
+    }
+
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+    use mockall::predicate::eq;
+
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+
+    use super::*;
+
+    #[test]
+    fn test_list_tasks() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"scripts": {"test": "mocha", "build": "tsc"}}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        mock_fs
+            .expect_read_dir()
+            .with(eq(Path::new("/project/node_modules/.bin")))
+            .returning(|_| Ok(vec!["tsc".to_string(), "mocha".to_string()]));
+        mock_fs
+            .expect_read_dir()
+            .with(eq(Path::new("/node_modules/.bin")))
+            .returning(|_| Ok(vec!["test".to_string()]));
+        mock_fs
+            .expect_read_dir()
+            .returning(|_| Err(eyre!("not found")));
+
+        assert_eq!(
+            list_tasks(&mock_fs, Path::new("/project/src")),
+            vec!["build", "test", "mocha", "tsc"]
+        );
+    }
+
+    #[test]
+    fn test_completions_include_tasks() {
+        for shell in [
+            CompletionShell::Bash,
+            CompletionShell::Zsh,
+            CompletionShell::Fish,
+        ] {
+            let mut out = Vec::new();
+            completions(shell, &mut out).unwrap();
+            let script = String::from_utf8(out).unwrap();
+
+            assert!(script.contains("doctor"), "{shell:?} misses commands");
+            assert!(script.contains("ny __complete"), "{shell:?} misses tasks");
+        }
+    }
+}
@@ -0,0 +1,503 @@
+use colored::Colorize;
+use eyre::{eyre, Report, Result};
+use std::path::Path;
+
+use crate::{
+    agent::Agent,
+    execute::Executor,
+    fs::{find_in_parents, Filesystem},
+    http::HttpClient,
+    manifest::PackageManifest,
+    node_version::{find_node_requirements, NodeRequirement},
+    npmrc::Registries,
+    semver::{Version, VersionReq},
+    tsconfig::find_tsconfig,
+};
+
+use super::which::{explain_agent, AgentReport};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Ok,
+    Warning,
+    Error,
+    Skipped,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Check {
+    pub status: Status,
+    pub message: String,
+    /// what user can do to fix the problem
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(message: impl Into<String>) -> Self {
+        Self {
+            status: Status::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn error(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: Status::Error,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn skipped(message: impl Into<String>) -> Self {
+        Self {
+            status: Status::Skipped,
+            message: message.into(),
+            fix: None,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn doctor(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    http_client: &dyn HttpClient,
+    cwd: &Path,
+    agent_override: Option<Agent>,
+    agent_setting: Option<Agent>,
+    settings_error: Option<&Report>,
+    offline: bool,
+) -> Result<()> {
+    let checks = diagnose(
+        executor,
+        fs,
+        http_client,
+        cwd,
+        agent_override,
+        agent_setting,
+        settings_error,
+        offline,
+    );
+
+    for check in &checks {
+        let symbol = match check.status {
+            Status::Ok => "✔".green(),
+            Status::Warning => "!".yellow(),
+            Status::Error => "✖".red(),
+            Status::Skipped => "-".dimmed(),
+        };
+        println!("{symbol} {}", check.message);
+        if let Some(fix) = &check.fix {
+            println!("  {}", fix.dimmed());
+        }
+    }
+
+    let errors = checks
+        .iter()
+        .filter(|check| check.status == Status::Error)
+        .count();
+    if errors > 0 {
+        return Err(eyre!("Found {errors} problem(s)"));
+    }
+
+    Ok(())
+}
+
+/// `settings_error` is reported as a problem, the rest of the checks runs with default settings
+#[allow(clippy::too_many_arguments)]
+pub fn diagnose(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    http_client: &dyn HttpClient,
+    cwd: &Path,
+    agent_override: Option<Agent>,
+    agent_setting: Option<Agent>,
+    settings_error: Option<&Report>,
+    offline: bool,
+) -> Vec<Check> {
+    let report = explain_agent(executor, fs, cwd, agent_override, agent_setting);
+    let package_json_path = find_in_parents(fs, cwd, "package.json");
+    let manifest = package_json_path
+        .as_ref()
+        .and_then(|path| PackageManifest::load(fs, path).ok());
+    let node_version = executor.which("node").and_then(|_| {
+        executor
+            .output("node", &["--version"])
+            .ok()
+            .and_then(|version| Version::parse(&version))
+    });
+    let project_dir = report
+        .lockfile
+        .as_ref()
+        .or(package_json_path.as_ref())
+        .and_then(|path| path.parent());
+
+    let mut checks = vec![];
+    if let Some(error) = settings_error {
+        checks.push(check_settings(error));
+    }
+    checks.extend(check_agent(&report, manifest.as_ref()));
+    let node_requirements =
+        find_node_requirements(fs, project_dir.unwrap_or(cwd), manifest.as_ref());
+    checks.extend(check_node(node_version.as_ref(), &node_requirements));
+    checks.extend(check_lockfiles(&report));
+    if let Some(project_dir) = project_dir {
+        checks.push(check_node_modules(
+            fs,
+            project_dir,
+            report.lockfile.as_deref(),
+        ));
+    }
+    checks.push(if offline {
+        Check::skipped("Registry check skipped (offline)")
+    } else {
+        let registries = Registries::from_env(fs, project_dir.unwrap_or(cwd));
+        check_registry(http_client, registries.default_registry())
+    });
+    checks.push(check_typescript(fs, cwd, manifest.as_ref()));
+
+    checks
+}
+
+fn check_settings(error: &Report) -> Check {
+    Check::error(
+        format!("Couldn't load settings: {error:#}"),
+        "Fix ny.toml, .nyrc, ~/.config/ny/config.toml or \"ny\" key in package.json",
+    )
+}
+
+fn check_agent(report: &AgentReport, manifest: Option<&PackageManifest>) -> Vec<Check> {
+    let Some(agent) = report.agent else {
+        return vec![Check::error(
+            "Couldn't find any lockfile",
+            "Run `ny --agent <npm|yarn|pnpm|bun> install` to create one",
+        )];
+    };
+    let program = agent.program();
+    let Some(binary) = &report.binary else {
+        return vec![Check::error(
+            format!("{program} is not installed"),
+            format!("To install it {}", agent.install_hint()),
+        )];
+    };
+    let version = report.binary_version.as_deref().and_then(Version::parse);
+    let mut checks = vec![Check::ok(format!(
+        "Using {program} {} ({})",
+        report
+            .binary_version
+            .as_deref()
+            .unwrap_or("unknown version"),
+        binary.display()
+    ))];
+
+    if let Some(field) = &report.package_manager_field {
+        match Agent::from_package_manager_field(field) {
+            Some((declared_agent, _)) if declared_agent != agent => checks.push(Check::warning(
+                format!("packageManager field declares {field} but {program} is used"),
+                "Remove stale lockfile or update packageManager field",
+            )),
+            Some((_, Some(declared_version))) if version.as_ref() != Some(&declared_version) => {
+                checks.push(Check::warning(
+                    format!(
+                        "packageManager field declares {field} but {program} {} is installed",
+                        report
+                            .binary_version
+                            .as_deref()
+                            .unwrap_or("unknown version")
+                    ),
+                    "Run `corepack enable` to use exactly the declared version",
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(range) = manifest.and_then(|manifest| manifest.engines.get(program)) {
+        if let (Some(req), Some(version)) = (VersionReq::parse(range), &version) {
+            if !req.matches(version) {
+                checks.push(Check::warning(
+                    format!("{program} {version} doesn't satisfy engines.{program}: {range}"),
+                    format!("Install {program} version matching {range}"),
+                ));
+            }
+        }
+    }
+
+    checks
+}
+
+fn check_node(node_version: Option<&Version>, requirements: &[NodeRequirement]) -> Vec<Check> {
+    let Some(node_version) = node_version else {
+        return vec![Check::error(
+            "Node.js is not installed",
+            "Install it from https://nodejs.org or with a version manager like nvm, fnm or volta",
+        )];
+    };
+    let mut checks = vec![Check::ok(format!("Using Node.js {node_version}"))];
+
+    for requirement in requirements {
+        if !requirement.req.matches(node_version) {
+            let NodeRequirement { source, range, .. } = requirement;
+            checks.push(Check::warning(
+                format!("Node.js {node_version} doesn't satisfy {source}: {range}"),
+                format!("Switch to Node.js version matching {range}, ex. with `nvm use`"),
+            ));
+        }
+    }
+
+    checks
+}
+
+fn check_lockfiles(report: &AgentReport) -> Vec<Check> {
+    let Some(lockfile) = &report.lockfile else {
+        return vec![];
+    };
+    let project_dir = lockfile.parent();
+
+    report
+        .other_lockfiles
+        .iter()
+        .map(|(agent, path)| {
+            if path.parent() == project_dir {
+                Check::error(
+                    format!(
+                        "Conflicting lockfiles: {} and {}",
+                        lockfile.display(),
+                        path.display()
+                    ),
+                    format!(
+                        "Remove the lockfile of the package manager you don't use, ex. `rm {}`",
+                        path.display()
+                    ),
+                )
+            } else {
+                Check::warning(
+                    format!(
+                        "Found {} lockfile in a parent directory: {}",
+                        agent.program(),
+                        path.display()
+                    ),
+                    "Make sure it's not a leftover, it's used when running ny outside of this project",
+                )
+            }
+        })
+        .collect()
+}
+
+fn check_node_modules(fs: &dyn Filesystem, project_dir: &Path, lockfile: Option<&Path>) -> Check {
+    let node_modules = project_dir.join("node_modules");
+    if !fs.exists(&node_modules) {
+        return Check::error("Dependencies are not installed", "Run `ny install`");
+    }
+
+    // files written by package managers at the end of the install
+    let markers = [
+        ".package-lock.json",
+        ".modules.yaml",
+        ".yarn-state.yml",
+        ".yarn-integrity",
+    ];
+    let installed_at = markers
+        .iter()
+        .map(|marker| node_modules.join(marker))
+        .find(|marker| fs.exists(marker))
+        .and_then(|marker| fs.modified(&marker).ok())
+        .or_else(|| fs.modified(&node_modules).ok());
+    let locked_at = lockfile.and_then(|lockfile| fs.modified(lockfile).ok());
+
+    match (installed_at, locked_at) {
+        (Some(installed_at), Some(locked_at)) if locked_at > installed_at => Check::warning(
+            "Dependencies are outdated, lockfile changed since the last install",
+            "Run `ny install`",
+        ),
+        _ => Check::ok("Dependencies are installed"),
+    }
+}
+
+/// Pings the registry configured in .npmrc
+fn check_registry(http_client: &dyn HttpClient, registry: &str) -> Check {
+    let ping_url = format!("{}/-/ping", registry.trim_end_matches('/'));
+    match http_client.request_if_success(&ping_url) {
+        Ok(true) => Check::ok(format!("Registry {registry} is reachable")),
+        _ => Check::error(
+            format!("Registry {registry} is not reachable"),
+            "Check your network connection and proxy settings or pass --offline to skip this check",
+        ),
+    }
+}
+
+fn check_typescript(fs: &dyn Filesystem, cwd: &Path, manifest: Option<&PackageManifest>) -> Check {
+    if let Some(tsconfig) = find_tsconfig(fs, cwd) {
+        return Check::ok(format!("TypeScript config: {}", tsconfig.display()));
+    }
+
+    if manifest.is_some_and(|manifest| manifest.has_dependency("typescript")) {
+        Check::warning(
+            "typescript is a dependency but there is no tsconfig.json",
+            "Run `ny tsc --init` to create one",
+        )
+    } else {
+        Check::ok("Not a TypeScript project")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    use mockall::predicate::eq;
+
+    use crate::commands::which::AgentSource;
+    use crate::fs::MockFilesystem;
+    use crate::http::MockHttpClient;
+
+    use super::*;
+
+    fn report(agent: Agent, version: &str) -> AgentReport {
+        AgentReport {
+            agent: Some(agent),
+            source: Some(AgentSource::Lockfile),
+            lockfile: Some(PathBuf::from("/project/pnpm-lock.yaml")),
+            binary: Some(PathBuf::from("/usr/bin/pnpm")),
+            binary_version: Some(version.to_string()),
+            package_manager_field: None,
+            other_lockfiles: vec![],
+        }
+    }
+
+    #[test]
+    fn test_check_agent_missing_binary() {
+        let mut report = report(Agent::Pnpm, "9.4.0");
+        report.binary = None;
+
+        let checks = check_agent(&report, None);
+
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, Status::Error);
+    }
+
+    #[test]
+    fn test_check_agent_package_manager_mismatch() {
+        let mut report = report(Agent::Pnpm, "8.15.0");
+        report.package_manager_field = Some("pnpm@9.4.0".to_string());
+        let manifest =
+            PackageManifest::from_value(&serde_json::json!({ "engines": { "pnpm": ">=9" } }));
+
+        let statuses = check_agent(&report, Some(&manifest))
+            .into_iter()
+            .map(|check| check.status)
+            .collect::<Vec<_>>();
+
+        assert_eq!(statuses, [Status::Ok, Status::Warning, Status::Warning]);
+    }
+
+    #[test]
+    fn test_check_node() {
+        let requirement = |source, range: &str| NodeRequirement {
+            source,
+            range: range.to_string(),
+            req: VersionReq::parse(range).unwrap(),
+        };
+        let node_version = Version::parse("20.11.0").unwrap();
+
+        let checks = check_node(Some(&node_version), &[requirement("engines.node", ">=18")]);
+        assert_eq!(checks.len(), 1);
+
+        let checks = check_node(
+            Some(&node_version),
+            &[
+                requirement("engines.node", ">=18"),
+                requirement(".nvmrc", "v18"),
+            ],
+        );
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[1].status, Status::Warning);
+    }
+
+    #[test]
+    fn test_check_lockfiles() {
+        let mut report = report(Agent::Pnpm, "9.4.0");
+        report.other_lockfiles = vec![
+            (Agent::Npm, PathBuf::from("/project/package-lock.json")),
+            (Agent::Yarn, PathBuf::from("/yarn.lock")),
+        ];
+
+        let statuses = check_lockfiles(&report)
+            .into_iter()
+            .map(|check| check.status)
+            .collect::<Vec<_>>();
+
+        assert_eq!(statuses, [Status::Error, Status::Warning]);
+    }
+
+    #[test]
+    fn test_check_node_modules_stale() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs
+            .expect_exists()
+            .returning(|path| path == Path::new("/project/node_modules"));
+        mock_fs
+            .expect_modified()
+            .with(eq(Path::new("/project/node_modules")))
+            .returning(|_| Ok(SystemTime::UNIX_EPOCH));
+        mock_fs
+            .expect_modified()
+            .with(eq(Path::new("/project/pnpm-lock.yaml")))
+            .returning(|_| Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(60)));
+
+        let check = check_node_modules(
+            &mock_fs,
+            Path::new("/project"),
+            Some(Path::new("/project/pnpm-lock.yaml")),
+        );
+
+        assert_eq!(check.status, Status::Warning);
+    }
+
+    #[test]
+    fn test_check_node_modules_missing() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|_| false);
+
+        let check = check_node_modules(&mock_fs, Path::new("/project"), None);
+
+        assert_eq!(check.status, Status::Error);
+    }
+
+    #[test]
+    fn test_check_registry() {
+        let mut mock_http_client = MockHttpClient::new();
+        mock_http_client
+            .expect_request_if_success()
+            .with(eq("https://npm.acme.dev/-/ping"))
+            .returning(|_| Err(eyre!("network error")));
+
+        assert_eq!(
+            check_registry(&mock_http_client, "https://npm.acme.dev/").status,
+            Status::Error
+        );
+    }
+
+    #[test]
+    fn test_check_typescript_missing_tsconfig() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|_| false);
+        let manifest = PackageManifest::from_value(&serde_json::json!({
+            "devDependencies": { "typescript": "^5" }
+        }));
+
+        let check = check_typescript(&mock_fs, Path::new("/project"), Some(&manifest));
+
+        assert_eq!(check.status, Status::Warning);
+    }
+}
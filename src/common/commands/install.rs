@@ -1,13 +1,29 @@
 use eyre::Result;
 
-use crate::{agent::Agent, execute::Executor};
-
-pub fn install(executor: &dyn Executor, agent: &Agent) -> Result<()> {
-    match agent {
-        Agent::Npm => executor.execute("npm", &["install"], None, true, false),
-        Agent::Yarn => executor.execute("yarn", &["install"], None, true, false),
-        Agent::Pnpm => executor.execute("pnpm", &["install"], None, true, false),
-        Agent::Bun => executor.execute("bun", &["install"], None, true, false),
+use crate::{agent::Agent, config::Settings, execute::Executor, semver::Version};
+
+pub fn install(executor: &dyn Executor, agent: &Agent, settings: &Settings) -> Result<()> {
+    let verbose = settings.verbose;
+    match (agent, settings.frozen) {
+        (Agent::Npm, false) => executor.execute("npm", &["install"], None, verbose, false),
+        (Agent::Npm, true) => executor.execute("npm", &["ci"], None, verbose, false),
+        (agent, false) => executor.execute(agent.program(), &["install"], None, verbose, false),
+        (agent, true) => {
+            let version = match agent {
+                Agent::Yarn => executor
+                    .output("yarn", &["--version"])
+                    .ok()
+                    .and_then(|version| Version::parse(&version)),
+                _ => None,
+            };
+            executor.execute(
+                agent.program(),
+                &["install", agent.frozen_lockfile_flag(version.as_ref())],
+                None,
+                verbose,
+                false,
+            )
+        }
     }
 }
 
@@ -30,7 +46,7 @@ mod tests {
             false,
         );
 
-        let result = install(&mock_executor, &Agent::Npm);
+        let result = install(&mock_executor, &Agent::Npm, &Settings::default());
 
         assert!(result.is_ok());
     }
@@ -47,7 +63,7 @@ mod tests {
             false,
         );
 
-        let result = install(&mock_executor, &Agent::Yarn);
+        let result = install(&mock_executor, &Agent::Yarn, &Settings::default());
 
         assert!(result.is_ok());
     }
@@ -64,7 +80,7 @@ mod tests {
             false,
         );
 
-        let result = install(&mock_executor, &Agent::Pnpm);
+        let result = install(&mock_executor, &Agent::Pnpm, &Settings::default());
 
         assert!(result.is_ok());
     }
@@ -81,8 +97,79 @@ mod tests {
             false,
         );
 
-        let result = install(&mock_executor, &Agent::Bun);
+        let result = install(&mock_executor, &Agent::Bun, &Settings::default());
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_install_frozen() {
+        let settings = Settings {
+            frozen: true,
+            ..Settings::default()
+        };
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "npm",
+            vec_of_strings!("ci"),
+            None,
+            true,
+            false,
+        );
+        expect_execute_once(
+            &mut mock_executor,
+            "pnpm",
+            vec_of_strings!("install", "--frozen-lockfile"),
+            None,
+            true,
+            false,
+        );
+
+        assert!(install(&mock_executor, &Agent::Npm, &settings).is_ok());
+        assert!(install(&mock_executor, &Agent::Pnpm, &settings).is_ok());
+    }
+
+    #[test]
+    fn test_install_frozen_yarn() {
+        let settings = Settings {
+            frozen: true,
+            ..Settings::default()
+        };
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_output()
+            .withf(|program, args| program == "yarn" && args == ["--version"])
+            .times(1)
+            .returning(|_, _| Ok("4.1.0\n".to_string()));
+        expect_execute_once(
+            &mut mock_executor,
+            "yarn",
+            vec_of_strings!("install", "--immutable"),
+            None,
+            true,
+            false,
+        );
+
+        assert!(install(&mock_executor, &Agent::Yarn, &settings).is_ok());
+    }
+
+    #[test]
+    fn test_install_quiet() {
+        let settings = Settings {
+            verbose: false,
+            ..Settings::default()
+        };
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "yarn",
+            vec_of_strings!("install"),
+            None,
+            false,
+            false,
+        );
+
+        assert!(install(&mock_executor, &Agent::Yarn, &settings).is_ok());
+    }
 }
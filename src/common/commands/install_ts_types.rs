@@ -1,18 +1,36 @@
 use colored::Colorize;
-use eyre::{Context, ContextCompat, Result};
+use eyre::{ContextCompat, Result};
 use std::path::Path;
 
 use crate::agent::Agent;
+use crate::config::Settings;
 use crate::execute::Executor;
 use crate::fs::{find_in_parents, Filesystem};
 use crate::http::HttpClient;
+use crate::manifest::PackageManifest;
+use crate::npmrc::Registries;
+use crate::semver::Version;
+use crate::tsconfig::{compiler_option_list, find_tsconfig, load_tsconfig};
 
 use super::add;
 
 pub fn check_if_ts_repo(fs: &dyn Filesystem, cwd: &Path) -> bool {
-    find_in_parents(fs, cwd, "tsconfig.json").is_some()
+    let Some(tsconfig_path) = find_tsconfig(fs, cwd) else {
+        return false;
+    };
+    if tsconfig_path.ends_with("jsconfig.json") {
+        // jsconfig is mostly used to configure editors, types matter only when JS files are type-checked
+        return load_tsconfig(fs, &tsconfig_path)
+            .ok()
+            .and_then(|tsconfig| tsconfig.get("compilerOptions")?.get("checkJs")?.as_bool())
+            .unwrap_or_default();
+    }
+
+    true
 }
 
+/// Installs missing @types of the packages allowed by `settings.types`. Doesn't check if types are enabled
+#[allow(clippy::too_many_arguments)]
 pub fn install_ts_types(
     executor: &dyn Executor,
     fs: &dyn Filesystem,
@@ -21,19 +39,47 @@ pub fn install_ts_types(
     cwd: &Path,
     packages: &[String],
     workspace_root: bool,
+    settings: &Settings,
 ) -> Result<()> {
-    let packages_missing_types = packages
-        .iter()
+    let packages_missing_types = settings
+        .types
+        .filter(packages)
+        .into_iter()
         .filter(|package| !package.starts_with("@types/"))
         .filter(|package| !check_if_package_has_types(fs, cwd, package).unwrap_or_default())
         .collect::<Vec<_>>();
+    // declared @types missing from node_modules (ex. before the first install) are installed with other
+    // dependencies, adding them again would overwrite their version ranges
+    let manifest = find_in_parents(fs, cwd, "package.json")
+        .and_then(|path| PackageManifest::load(fs, &path).ok());
+    let registries = Registries::from_env(fs, cwd);
 
     let packages_to_install = packages_missing_types
         .iter()
-        .map(|package| package_name_to_types_package_name(package.to_string()))
-        .filter(|package| !check_if_package_exists_locally(fs, cwd, package).unwrap_or_default())
-        .filter(|package| {
-            check_if_package_exists_in_registry(http_client, package).unwrap_or_default()
+        .map(|package| {
+            (
+                package,
+                package_name_to_types_package_name(package.to_string()),
+            )
+        })
+        .filter(|(_, types_package)| {
+            !check_if_package_exists_locally(fs, cwd, types_package).unwrap_or_default()
+                && !manifest
+                    .as_ref()
+                    .is_some_and(|manifest| manifest.has_dependency(types_package))
+        })
+        .filter_map(|(package, types_package)| {
+            // skip packages that don't have @types in the registry
+            let available_versions =
+                get_package_versions_from_registry(http_client, &registries, &types_package)
+                    .ok()??;
+            let installed_version = get_installed_package_version(fs, cwd, package).ok();
+
+            Some(types_package_with_version(
+                types_package,
+                installed_version.as_ref(),
+                &available_versions,
+            ))
         })
         .collect::<Vec<_>>();
 
@@ -59,8 +105,10 @@ pub fn install_ts_types(
             workspace_root,
             &packages_to_install,
             true, // silence output of PM's add command. The command itself is still printed.
+            settings,
         )?;
     }
+    suggest_runtime_types_package(executor, fs, http_client, cwd, agent, settings);
 
     Ok(())
 }
@@ -77,18 +125,172 @@ fn package_name_to_types_package_name(mut package: String) -> String {
     format!("@types/{package}")
 }
 
-fn check_if_package_has_types(fs: &dyn Filesystem, cwd: &Path, package: &str) -> Result<bool> {
-    let package_json_sub_path = Path::new("node_modules").join(package).join("package.json");
+// runtime executing the project and packages providing typings of its globals
+fn runtime_types_packages(agent: &Agent) -> (&'static str, &'static [&'static str]) {
+    match agent {
+        Agent::Bun => ("bun", &["@types/bun", "bun-types"]),
+        _ => ("node", &["@types/node"]),
+    }
+}
+
+// Only suggests installing runtime typings as the project might target other environment than the package
+// manager runs in. Version is matched to the runtime the same way library typings are matched to libraries
+fn suggest_runtime_types_package(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    http_client: &dyn HttpClient,
+    cwd: &Path,
+    agent: &Agent,
+    settings: &Settings,
+) {
+    let (runtime, _) = runtime_types_packages(agent);
+    if !settings.types.allows(runtime) {
+        return;
+    }
+    let Some(types_package) = find_missing_runtime_types_package(fs, cwd, agent) else {
+        return;
+    };
+
+    let runtime_version = executor
+        .output(runtime, &["--version"])
+        .ok()
+        .and_then(|output| Version::parse(&output));
+    let registries = Registries::from_env(fs, cwd);
+    let available_versions = runtime_version
+        .as_ref()
+        .and_then(|_| {
+            get_package_versions_from_registry(http_client, &registries, &types_package).ok()?
+        })
+        .unwrap_or_default();
+    let types_package =
+        types_package_with_version(types_package, runtime_version.as_ref(), &available_versions);
+
+    println!();
+    println!("Typings of {runtime} globals are missing.");
+    println!(
+        "Run {} to install them.",
+        format!("ny add -D {types_package}").bold()
+    );
+}
+
+// Typings of the runtime globals (like `process` or `Bun`) are not pulled by any library but adding
+// packages relying on them fails type-checking. Returns runtime typings package when it's missing.
+fn find_missing_runtime_types_package(
+    fs: &dyn Filesystem,
+    cwd: &Path,
+    agent: &Agent,
+) -> Option<String> {
+    let (runtime, types_packages) = runtime_types_packages(agent);
+    if types_packages
+        .iter()
+        .any(|package| check_if_package_exists_locally(fs, cwd, package).unwrap_or_default())
+    {
+        return None;
+    }
+
+    let tsconfig_path = find_tsconfig(fs, cwd)?;
+    let tsconfig = load_tsconfig(fs, &tsconfig_path).ok()?;
+
+    if let Some(types) = compiler_option_list(&tsconfig, "types") {
+        // `types` limits which global typings are visible so we respect explicit choice
+        if !types
+            .iter()
+            .any(|t| t == runtime || types_packages.contains(&t.as_str()))
+        {
+            return None;
+        }
+    } else if let Some(lib) = compiler_option_list(&tsconfig, "lib") {
+        // code meant for browsers has no use for runtime typings
+        if lib.iter().any(|l| l.starts_with("dom")) {
+            return None;
+        }
+    }
+
+    Some(types_packages[0].to_string())
+}
+
+// reverse of `package_name_to_types_package_name` ex. "@types/foo__bar" -> "@foo/bar"
+pub(crate) fn types_package_name_to_package_name(types_package: &str) -> String {
+    let package = types_package.trim_start_matches("@types/");
+
+    match package.split_once("__") {
+        Some((scope, name)) => format!("@{scope}/{name}"),
+        None => package.to_string(),
+    }
+}
+
+// strips version (or tag) from a package specifier ex. "@foo/bar@^1.0.0" -> "@foo/bar"
+pub(crate) fn package_name_without_version(package: &str) -> &str {
+    let version_separator = if let Some(scoped) = package.strip_prefix('@') {
+        scoped.find('@').map(|i| i + 1)
+    } else {
+        package.find('@')
+    };
+
+    match version_separator {
+        Some(i) => &package[..i],
+        None => package,
+    }
+}
+
+// picks @types version that matches installed library: same major and minor if possible, otherwise same major.
+// When nothing matches (or library version is unknown) latest version will be installed.
+fn types_package_with_version(
+    types_package: String,
+    installed_version: Option<&Version>,
+    available_versions: &[Version],
+) -> String {
+    let Some(installed_version) = installed_version else {
+        return types_package;
+    };
+    let stable_versions = available_versions.iter().filter(|v| !v.is_prerelease());
+
+    let same_minor = stable_versions
+        .clone()
+        .filter(|v| v.major == installed_version.major && v.minor == installed_version.minor)
+        .max();
+    let same_major = stable_versions
+        .filter(|v| v.major == installed_version.major)
+        .max();
+
+    match same_minor.or(same_major) {
+        Some(version) => format!("{types_package}@{version}"),
+        None => types_package,
+    }
+}
+
+fn read_installed_package_json(
+    fs: &dyn Filesystem,
+    cwd: &Path,
+    package: &str,
+) -> Result<PackageManifest> {
+    let package_json_sub_path = Path::new("node_modules")
+        .join(package_name_without_version(package))
+        .join("package.json");
     let package_json_path = find_in_parents(fs, cwd, package_json_sub_path.to_str().unwrap())
         .context("can't find package root")?;
-    let package_json = fs
-        .read_to_string(package_json_path.as_path())
-        .context("can't read package.json")?;
-    let package_json: serde_json::Value = serde_json::from_str(&package_json).unwrap();
-    let types = package_json.get("types");
-    let typings = package_json.get("typings");
-
-    Ok(types.is_some() || typings.is_some())
+
+    PackageManifest::load(fs, &package_json_path)
+}
+
+fn check_if_package_has_types(fs: &dyn Filesystem, cwd: &Path, package: &str) -> Result<bool> {
+    let manifest = read_installed_package_json(fs, cwd, package)?;
+
+    Ok(manifest.has_types())
+}
+
+fn get_installed_package_version(
+    fs: &dyn Filesystem,
+    cwd: &Path,
+    package: &str,
+) -> Result<Version> {
+    let manifest = read_installed_package_json(fs, cwd, package)?;
+
+    manifest
+        .version
+        .as_deref()
+        .and_then(Version::parse)
+        .context("package.json doesn't contain valid version")
 }
 
 fn check_if_package_exists_locally(fs: &dyn Filesystem, cwd: &Path, package: &str) -> Result<bool> {
@@ -99,23 +301,35 @@ fn check_if_package_exists_locally(fs: &dyn Filesystem, cwd: &Path, package: &st
     Ok(true)
 }
 
+// returns None if package doesn't exist in the registry
 // @todo: this should be parallelized
-fn check_if_package_exists_in_registry(
+fn get_package_versions_from_registry(
     http_client: &dyn HttpClient,
+    registries: &Registries,
     package: &str,
-) -> Result<bool> {
-    let url = format!("https://registry.npmjs.org/{package}");
-    http_client.request_if_success(&url)
+) -> Result<Option<Vec<Version>>> {
+    let Some(metadata) = http_client.request_json(&registries.package_url(package))? else {
+        return Ok(None);
+    };
+
+    let versions = metadata
+        .get("versions")
+        .and_then(|versions| versions.as_object())
+        .map(|versions| versions.keys().filter_map(|v| Version::parse(v)).collect())
+        .unwrap_or_default();
+
+    Ok(Some(versions))
 }
 
 #[cfg(test)]
 mod tests {
-    use mockall::predicate::eq;
-
     use crate::{
         execute::{expect_execute_once, MockExecutor},
         fs::{test_utils::expect_file, MockFilesystem},
-        http::{test_utils::expect_package_exist_in_registry, MockHttpClient},
+        http::{
+            test_utils::{expect_package_exist_in_registry, expect_package_versions_in_registry},
+            MockHttpClient,
+        },
         utils::vec_of_strings,
     };
 
@@ -137,6 +351,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_types_package_name_to_package_name() {
+        assert_eq!(types_package_name_to_package_name("@types/foo"), "foo");
+        assert_eq!(
+            types_package_name_to_package_name("@types/foo__bar"),
+            "@foo/bar"
+        );
+    }
+
+    #[test]
+    fn test_package_name_without_version() {
+        assert_eq!(package_name_without_version("foo"), "foo");
+        assert_eq!(package_name_without_version("foo@^1.0.0"), "foo");
+        assert_eq!(package_name_without_version("@foo/bar"), "@foo/bar");
+        assert_eq!(package_name_without_version("@foo/bar@latest"), "@foo/bar");
+    }
+
+    #[test]
+    fn test_types_package_with_version() {
+        let versions = [
+            "17.0.2",
+            "18.2.0",
+            "18.2.7",
+            "18.3.1",
+            "19.0.0",
+            "18.2.8-beta.0",
+        ]
+        .iter()
+        .map(|v| Version::parse(v).unwrap())
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            types_package_with_version(
+                "@types/react".to_string(),
+                Version::parse("18.2.0").as_ref(),
+                &versions
+            ),
+            "@types/react@18.2.7"
+        );
+        assert_eq!(
+            types_package_with_version(
+                "@types/react".to_string(),
+                Version::parse("18.1.0").as_ref(),
+                &versions
+            ),
+            "@types/react@18.3.1"
+        );
+        assert_eq!(
+            types_package_with_version(
+                "@types/react".to_string(),
+                Version::parse("20.0.0").as_ref(),
+                &versions
+            ),
+            "@types/react"
+        );
+        assert_eq!(
+            types_package_with_version("@types/react".to_string(), None, &versions),
+            "@types/react"
+        );
+    }
+
     #[test]
     fn test_check_if_ts_repo() {
         let mut mock_fs = MockFilesystem::new();
@@ -146,22 +421,51 @@ mod tests {
             "".to_owned(),
         );
 
-        assert_eq!(check_if_ts_repo(&mock_fs, Path::new("/project")), true);
+        assert!(check_if_ts_repo(&mock_fs, Path::new("/project")));
     }
 
     #[test]
     fn test_check_if_not_ts_repo() {
         let mut mock_fs = MockFilesystem::new();
-        mock_fs
-            .expect_exists()
-            .with(eq(Path::new("/project/tsconfig.json").to_owned()))
-            .returning(|_| false);
-        mock_fs
-            .expect_exists()
-            .with(eq(Path::new("/tsconfig.json").to_owned()))
-            .returning(|_| false);
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert!(!check_if_ts_repo(&mock_fs, Path::new("/project")));
+    }
+
+    #[test]
+    fn test_check_if_ts_repo_ignores_parent_projects() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|path| {
+            path == Path::new("/project/yarn.lock") || path == Path::new("/tsconfig.json")
+        });
 
-        assert_eq!(check_if_ts_repo(&mock_fs, Path::new("/project")), false);
+        assert!(!check_if_ts_repo(&mock_fs, Path::new("/project")));
+    }
+
+    #[test]
+    fn test_check_if_ts_repo_jsconfig() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/jsconfig.json").to_owned(),
+            r#"{ "compilerOptions": { "checkJs": true } }"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert!(check_if_ts_repo(&mock_fs, Path::new("/project")));
+    }
+
+    #[test]
+    fn test_check_if_ts_repo_jsconfig_without_check_js() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/jsconfig.json").to_owned(),
+            r#"{ "compilerOptions": {} }"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert!(!check_if_ts_repo(&mock_fs, Path::new("/project")));
     }
 
     #[test]
@@ -173,10 +477,9 @@ mod tests {
             r#"{"types": "index.d.ts"}"#.to_owned(),
         );
 
-        assert_eq!(
+        assert!(
             check_if_package_has_types(&mock_fs, Path::new("/project"), "package")
-                .unwrap_or_default(),
-            true
+                .unwrap_or_default()
         );
     }
     #[test]
@@ -188,10 +491,24 @@ mod tests {
             r#"{"typings": "index.d.ts"}"#.to_owned(),
         );
 
-        assert_eq!(
+        assert!(
             check_if_package_has_types(&mock_fs, Path::new("/project"), "package")
-                .unwrap_or_default(),
-            true
+                .unwrap_or_default()
+        );
+    }
+
+    #[test]
+    fn test_check_if_package_has_types_in_exports() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/node_modules/package/package.json").to_owned(),
+            r#"{"exports": {".": {"types": "./index.d.ts", "import": "./index.js"}}}"#.to_owned(),
+        );
+
+        assert!(
+            check_if_package_has_types(&mock_fs, Path::new("/project"), "package")
+                .unwrap_or_default()
         );
     }
 
@@ -204,10 +521,9 @@ mod tests {
             r#"{}"#.to_owned(),
         );
 
-        assert_eq!(
-            check_if_package_has_types(&mock_fs, Path::new("/project"), "package")
-                .unwrap_or_default(),
-            false
+        assert!(
+            !check_if_package_has_types(&mock_fs, Path::new("/project"), "package")
+                .unwrap_or_default()
         );
     }
 
@@ -260,8 +576,185 @@ mod tests {
             &mock_http_client,
             &agent,
             Path::new("/project"),
-            &vec_of_strings!["package-a", "package-b", "package-c"],
+            &["package-a", "package-b", "package-c"].map(String::from),
+            false,
+            &Settings::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_integration_install_ts_types_matching_version() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/node_modules/react/package.json").to_owned(),
+            r#"{"version": "18.2.0"}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "pnpm",
+            vec_of_strings!["add", "-D", "@types/react@18.2.79"],
+            None,
+            true,
+            true,
+        );
+
+        let mut mock_http_client = MockHttpClient::new();
+        expect_package_versions_in_registry(
+            &mut mock_http_client,
+            "@types/react",
+            Some(&["18.2.0", "18.2.79", "18.3.12", "19.0.1"]),
+        );
+
+        install_ts_types(
+            &mock_executor,
+            &mock_fs,
+            &mock_http_client,
+            &Agent::Pnpm,
+            Path::new("/project"),
+            &["react@18.2"].map(String::from),
+            false,
+            &Settings::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_find_missing_runtime_types_package() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/tsconfig.json").to_owned(),
+            r#"{ "compilerOptions": { "lib": ["ES2022"] } }"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert_eq!(
+            find_missing_runtime_types_package(&mock_fs, Path::new("/project"), &Agent::Npm),
+            Some("@types/node".to_string())
+        );
+        assert_eq!(
+            find_missing_runtime_types_package(&mock_fs, Path::new("/project"), &Agent::Bun),
+            Some("@types/bun".to_string())
+        );
+    }
+
+    #[test]
+    fn test_find_missing_runtime_types_package_respects_tsconfig() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/tsconfig.json").to_owned(),
+            r#"{
+                // only vite globals are visible
+                "compilerOptions": { "types": ["vite/client"], },
+            }"#
+            .to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert_eq!(
+            find_missing_runtime_types_package(&mock_fs, Path::new("/project"), &Agent::Npm),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_missing_runtime_types_package_browser_lib() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/tsconfig.json").to_owned(),
+            r#"{ "compilerOptions": { "lib": ["ES2020", "DOM", "DOM.Iterable"] } }"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert_eq!(
+            find_missing_runtime_types_package(&mock_fs, Path::new("/project"), &Agent::Npm),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_missing_runtime_types_package_already_installed() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/node_modules/bun-types/package.json").to_owned(),
+            r#"{}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert_eq!(
+            find_missing_runtime_types_package(&mock_fs, Path::new("/project"), &Agent::Bun),
+            None
+        );
+    }
+
+    #[test]
+    fn test_integration_runtime_types_are_only_suggested() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/tsconfig.json").to_owned(),
+            r#"{}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        // nothing is installed
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_output()
+            .withf(|program, args| program == "node" && args == ["--version"])
+            .times(1)
+            .returning(|_, _| Ok("v20.11.1\n".to_string()));
+
+        let mut mock_http_client = MockHttpClient::new();
+        expect_package_versions_in_registry(
+            &mut mock_http_client,
+            "@types/node",
+            Some(&["20.11.30", "22.5.0"]),
+        );
+
+        install_ts_types(
+            &mock_executor,
+            &mock_fs,
+            &mock_http_client,
+            &Agent::Npm,
+            Path::new("/project"),
+            &[],
             false,
+            &Settings::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_integration_runtime_types_respect_policy() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/tsconfig.json").to_owned(),
+            r#"{}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        let mut settings = Settings::default();
+        settings.types.deny = vec_of_strings!["node"];
+
+        // neither node version nor registry is checked
+        install_ts_types(
+            &MockExecutor::new(),
+            &mock_fs,
+            &MockHttpClient::new(),
+            &Agent::Npm,
+            Path::new("/project"),
+            &[],
+            false,
+            &settings,
         )
         .unwrap();
     }
@@ -290,8 +783,9 @@ mod tests {
             &mock_http_client,
             &agent,
             Path::new("/project"),
-            &vec_of_strings!["package-a"],
+            &["package-a"].map(String::from),
             false,
+            &Settings::default(),
         )
         .unwrap();
     }
@@ -305,6 +799,7 @@ mod tests {
             Path::new("/project/node_modules/package-a/package.json").to_owned(),
             r#"{"types":"exists"}"#.to_owned(),
         );
+        mock_fs.expect_exists().returning(|_| false);
 
         let mock_executor = MockExecutor::new();
         let mock_http_client = MockHttpClient::new();
@@ -316,8 +811,43 @@ mod tests {
             &mock_http_client,
             &agent,
             Path::new("/project"),
-            &vec_of_strings!["package-a"],
+            &["package-a"].map(String::from),
+            false,
+            &Settings::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_integration_types_from_scoped_registry() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/node_modules/package-a/package.json").to_owned(),
+            r#"{}"#.to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/.npmrc").to_owned(),
+            "@types:registry=https://mirror.dev/npm/".to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        let mut mock_http_client = MockHttpClient::new();
+        mock_http_client
+            .expect_request_json()
+            .withf(|url| url == "https://mirror.dev/npm/@types/package-a")
+            .times(1)
+            .returning(|_| Ok(None));
+
+        install_ts_types(
+            &MockExecutor::new(),
+            &mock_fs,
+            &mock_http_client,
+            &Agent::Npm,
+            Path::new("/project"),
+            &["package-a"].map(String::from),
             false,
+            &Settings::default(),
         )
         .unwrap();
     }
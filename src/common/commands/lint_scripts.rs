@@ -0,0 +1,217 @@
+use colored::Colorize;
+use eyre::{eyre, ContextCompat, Result};
+use std::path::Path;
+
+use crate::fs::{find_in_parents, Filesystem};
+use crate::invocations::{find_invocations, rewrite_invocations_to_ny};
+use crate::manifest::PackageManifest;
+use crate::manifest_editor::ManifestEditor;
+
+use super::run::load_script;
+
+/// Script calling a package manager directly and its package manager agnostic version
+#[derive(Debug, PartialEq)]
+struct Finding {
+    name: String,
+    script: String,
+    fixed: String,
+}
+
+/// Package manager call left as is because it passes options to npm itself, ex. `npm run build --watch`
+#[derive(Debug, PartialEq)]
+struct Unfixable {
+    name: String,
+    command: String,
+}
+
+/// Reports scripts calling package managers directly (ex. `yarn build && npm run test`) which could use ny
+/// instead. With `fix` rewrites them in package.json (unless it's a `dry_run`), otherwise fails when anything
+/// was found
+pub fn lint_scripts(fs: &dyn Filesystem, cwd: &Path, fix: bool, dry_run: bool) -> Result<()> {
+    let path = find_in_parents(fs, cwd, "package.json").with_context(|| {
+        format!("Couldn't find package.json in the current directory: {cwd:?} or its parents.")
+    })?;
+    let mut editor = ManifestEditor::load(fs, &path)?;
+    let findings = find_fixable_scripts(&editor.manifest());
+    let unfixable = find_unfixable_calls(&editor.manifest());
+
+    for call in &unfixable {
+        println!(
+            "{}",
+            format!(
+                "{}: `{}` is left as is, npm takes options before `--` for itself. Pass them after `--` to reach the script",
+                call.name, call.command
+            )
+            .yellow()
+        );
+    }
+    if findings.is_empty() {
+        if unfixable.is_empty() {
+            println!("Scripts don't call package managers directly");
+        }
+        return Ok(());
+    }
+
+    for finding in &findings {
+        println!("{}", finding.name.bold());
+        println!("  {}", format!("- {}", finding.script).red());
+        println!("  {}", format!("+ {}", finding.fixed).green());
+    }
+
+    if !fix {
+        return Err(eyre!(
+            "{} script(s) call package managers directly, run `ny lint-scripts --fix` to rewrite them",
+            findings.len()
+        ));
+    }
+    if dry_run {
+        println!("Would rewrite {} script(s)", findings.len());
+        return Ok(());
+    }
+
+    for finding in &findings {
+        editor.set(
+            &["scripts", &finding.name],
+            serde_json::Value::String(finding.fixed.clone()),
+        )?;
+    }
+    editor.save(fs, &path)?;
+    println!("Rewrote {} script(s)", findings.len());
+
+    Ok(())
+}
+
+fn find_fixable_scripts(manifest: &PackageManifest) -> Vec<Finding> {
+    let names: Vec<String> = manifest.scripts.keys().cloned().collect();
+
+    names
+        .iter()
+        .filter_map(|name| {
+            let script = load_script(manifest, name)?;
+            let fixed = rewrite_invocations_to_ny(&script, &names);
+            (fixed != script).then(|| Finding {
+                name: name.clone(),
+                script,
+                fixed,
+            })
+        })
+        .collect()
+}
+
+fn find_unfixable_calls(manifest: &PackageManifest) -> Vec<Unfixable> {
+    let names: Vec<String> = manifest.scripts.keys().cloned().collect();
+
+    manifest
+        .scripts
+        .iter()
+        .flat_map(|(name, script)| {
+            find_invocations(script, &names)
+                .into_iter()
+                .filter(|invocation| !invocation.agent_args.is_empty())
+                .map(|invocation| Unfixable {
+                    name: name.clone(),
+                    command: script[invocation.range].to_string(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::fs::{
+        test_utils::{expect_file, expect_write},
+        MockFilesystem,
+    };
+
+    use super::*;
+
+    const PACKAGE_JSON: &str = r#"{
+  "scripts": {
+    "build": "tsc",
+    "ci": "yarn build && npm run test -- --run",
+    "test": "vitest"
+  }
+}
+"#;
+
+    #[test]
+    fn test_find_fixable_scripts() {
+        let manifest = PackageManifest::parse(PACKAGE_JSON).unwrap();
+
+        assert_eq!(
+            find_fixable_scripts(&manifest),
+            vec![Finding {
+                name: "ci".to_string(),
+                script: "yarn build && npm run test -- --run".to_string(),
+                fixed: "ny build && ny test --run".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_npm_options_are_not_fixed() {
+        let manifest = PackageManifest::parse(
+            r#"{ "scripts": { "build": "tsc", "dev": "npm run build --watch" } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(find_fixable_scripts(&manifest), vec![]);
+        assert_eq!(
+            find_unfixable_calls(&manifest),
+            vec![Unfixable {
+                name: "dev".to_string(),
+                command: "npm run build --watch".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lint_scripts() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            PACKAGE_JSON.to_string(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert!(lint_scripts(&mock_fs, Path::new("/project"), false, false).is_err());
+    }
+
+    #[test]
+    fn test_lint_scripts_fix() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            PACKAGE_JSON.to_string(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        expect_write(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            PACKAGE_JSON.replace(
+                "yarn build && npm run test -- --run",
+                "ny build && ny test --run",
+            ),
+        );
+
+        lint_scripts(&mock_fs, Path::new("/project"), true, false).unwrap();
+    }
+
+    #[test]
+    fn test_lint_scripts_fix_dry_run() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            PACKAGE_JSON.to_string(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        // no writes are expected
+        lint_scripts(&mock_fs, Path::new("/project"), true, true).unwrap();
+    }
+}
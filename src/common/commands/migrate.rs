@@ -0,0 +1,407 @@
+use eyre::{Context, ContextCompat, Result};
+use std::path::{Path, PathBuf};
+
+use crate::agent::Agent;
+use crate::config::Settings;
+use crate::execute::Executor;
+use crate::fs::{find_in_parents, Filesystem};
+use crate::invocations::rewrite_invocations;
+use crate::manifest_editor::ManifestEditor;
+use crate::semver::Version;
+
+const PNPM_WORKSPACE: &str = "pnpm-workspace.yaml";
+// yarn Plug'n'Play loaders break resolution of packages installed by other package managers
+const PNP_FILES: &[&str] = &[".pnp.cjs", ".pnp.loader.mjs"];
+
+#[derive(Debug, PartialEq)]
+enum FileChange {
+    Create { path: PathBuf, contents: String },
+    Update { path: PathBuf, contents: String },
+    Remove(PathBuf),
+    RemoveDir(PathBuf),
+}
+
+/// Switches the project to another package manager: moves workspaces config between package.json and
+/// pnpm-workspace.yaml, updates `packageManager` field and package manager calls in scripts, installs
+/// dependencies and only then removes lockfiles of other package managers, so they're kept when the install
+/// fails. With `dry_run` only prints the changes
+pub fn migrate(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    cwd: &Path,
+    target: &Agent,
+    settings: &Settings,
+    dry_run: bool,
+) -> Result<()> {
+    // lockfile of a workspace lives next to the root package.json
+    let project_dir = match Agent::recognize_lockfile(fs, cwd) {
+        Some((_, lockfile)) => lockfile.parent().map(Path::to_path_buf),
+        None => find_in_parents(fs, cwd, "package.json")
+            .and_then(|path| path.parent().map(Path::to_path_buf)),
+    }
+    .context("Couldn't find package.json")?;
+    let program = target.program();
+
+    let changes = plan_migration(executor, fs, &project_dir, target)?;
+    if changes.is_empty() && Agent::recognize_in_dir(fs, &project_dir) == Some(*target) {
+        println!("Project already uses {program}, nothing to migrate");
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("Migrating to {program} would change:");
+        print_changes(&changes, &project_dir);
+        println!("and run `{program} install`");
+        return Ok(());
+    }
+
+    // summary goes first, a failed install stops ny right away
+    println!("Migrating to {program}:");
+    print_changes(&changes, &project_dir);
+
+    for change in &changes {
+        match change {
+            FileChange::Create { path, contents } | FileChange::Update { path, contents } => fs
+                .write(path, contents)
+                .with_context(|| format!("Couldn't write {path:?}"))?,
+            FileChange::RemoveDir(path) => fs
+                .remove_dir_all(path)
+                .with_context(|| format!("Couldn't remove {path:?}"))?,
+            FileChange::Remove(_) => {}
+        }
+    }
+    executor.execute(program, &["install"], None, settings.verbose, false)?;
+    for change in &changes {
+        if let FileChange::Remove(path) = change {
+            fs.remove_file(path)
+                .with_context(|| format!("Couldn't remove {path:?}"))?;
+        }
+    }
+
+    println!("Migrated to {program}");
+
+    Ok(())
+}
+
+fn plan_migration(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    project_dir: &Path,
+    target: &Agent,
+) -> Result<Vec<FileChange>> {
+    let mut changes = vec![];
+    let target_version = installed_version(executor, target);
+
+    let mut leaves_foreign_layout = false;
+    for (agent, lockfile) in Agent::find_all_lockfiles(fs, project_dir) {
+        if &agent != target && lockfile.parent() == Some(project_dir) {
+            leaves_foreign_layout |= agent == Agent::Pnpm;
+            changes.push(FileChange::Remove(lockfile));
+        }
+    }
+    if target != &Agent::Yarn {
+        for file in PNP_FILES {
+            let path = project_dir.join(file);
+            if fs.exists(&path) {
+                leaves_foreign_layout = true;
+                changes.push(FileChange::Remove(path));
+            }
+        }
+    }
+    // node_modules of pnpm is made of symlinks into its store and yarn PnP keeps only a few packages there,
+    // other package managers would build on top of them
+    let node_modules = project_dir.join("node_modules");
+    if leaves_foreign_layout && fs.exists(&node_modules) {
+        changes.push(FileChange::RemoveDir(node_modules));
+    }
+
+    let package_json = project_dir.join("package.json");
+    let mut editor = ManifestEditor::load(fs, &package_json)?;
+    let original = editor.to_string();
+    let manifest = editor.manifest();
+
+    let workspace_path = project_dir.join(PNPM_WORKSPACE);
+    if target == &Agent::Pnpm {
+        if !manifest.workspaces.is_empty() && !fs.exists(&workspace_path) {
+            changes.push(FileChange::Create {
+                path: workspace_path,
+                contents: pnpm_workspace_yaml(&manifest.workspaces),
+            });
+            editor.delete(&["workspaces"]);
+        }
+    } else if fs.exists(&workspace_path) {
+        let (packages, has_other_settings) = read_pnpm_workspace(fs, &workspace_path)?;
+        if manifest.workspaces.is_empty() && !packages.is_empty() {
+            editor.set(&["workspaces"], serde_json::json!(packages))?;
+        }
+        // other settings (ex. catalogs) have no counterpart in package.json so the file stays
+        if !has_other_settings {
+            changes.push(FileChange::Remove(workspace_path));
+        }
+    }
+
+    let script_names: Vec<String> = manifest.scripts.keys().cloned().collect();
+    for (name, script) in &manifest.scripts {
+        let rewritten = rewrite_invocations(script, &script_names, target, target_version.as_ref());
+        if &rewritten != script {
+            editor.set(&["scripts", name], serde_json::Value::String(rewritten))?;
+        }
+    }
+
+    let pinned_agent = manifest
+        .package_manager
+        .as_deref()
+        .and_then(Agent::from_package_manager_field)
+        .map(|(agent, _)| agent);
+    if pinned_agent.is_some_and(|agent| &agent != target) {
+        match &target_version {
+            Some(version) => editor.set(
+                &["packageManager"],
+                serde_json::Value::String(format!("{}@{version}", target.program())),
+            )?,
+            None => {
+                editor.delete(&["packageManager"]);
+            }
+        }
+    }
+
+    let contents = editor.to_string();
+    if contents != original {
+        changes.push(FileChange::Update {
+            path: package_json,
+            contents,
+        });
+    }
+
+    Ok(changes)
+}
+
+fn installed_version(executor: &dyn Executor, agent: &Agent) -> Option<Version> {
+    let output = executor.output(agent.program(), &["--version"]).ok()?;
+    Version::parse(output.trim())
+}
+
+/// Workspace globs and whether the file has any other settings
+fn read_pnpm_workspace(fs: &dyn Filesystem, path: &Path) -> Result<(Vec<String>, bool)> {
+    let contents = fs.read_to_string(path)?;
+    let config: serde_json::Value =
+        serde_yaml::from_str(&contents).with_context(|| format!("Couldn't parse {path:?}"))?;
+    let packages = config
+        .get("packages")
+        .and_then(|packages| packages.as_array())
+        .map(|packages| {
+            packages
+                .iter()
+                .filter_map(|glob| glob.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    let has_other_settings = config
+        .as_object()
+        .is_some_and(|config| config.keys().any(|key| key != "packages"));
+
+    Ok((packages, has_other_settings))
+}
+
+fn pnpm_workspace_yaml(globs: &[String]) -> String {
+    let mut yaml = "packages:\n".to_string();
+    for glob in globs {
+        yaml.push_str(&format!("  - '{}'\n", glob.replace('\'', "''")));
+    }
+
+    yaml
+}
+
+fn print_changes(changes: &[FileChange], project_dir: &Path) {
+    if changes.is_empty() {
+        println!("  no files");
+    }
+    for change in changes {
+        let (action, path) = match change {
+            FileChange::Create { path, .. } => ("created", path),
+            FileChange::Update { path, .. } => ("updated", path),
+            FileChange::Remove(path) | FileChange::RemoveDir(path) => ("removed", path),
+        };
+        let path = path.strip_prefix(project_dir).unwrap_or(path);
+        println!("  {action} {}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+    use mockall::{predicate::eq, Sequence};
+
+    use super::*;
+    use crate::execute::MockExecutor;
+    use crate::fs::{
+        test_utils::{expect_file, expect_write},
+        MockFilesystem,
+    };
+
+    fn expect_remove(mock_fs: &mut MockFilesystem, path: &str, seq: &mut Sequence) {
+        mock_fs
+            .expect_remove_file()
+            .with(eq(PathBuf::from(path)))
+            .times(1)
+            .in_sequence(seq)
+            .returning(|_| Ok(()));
+    }
+
+    #[test]
+    fn test_migrate_yarn_to_pnpm() {
+        let mut mock_fs = MockFilesystem::new();
+        let mut mock_executor = MockExecutor::new();
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            r#"{
+  "packageManager": "yarn@4.1.0",
+  "workspaces": ["packages/*"],
+  "scripts": {
+    "build": "tsc",
+    "ci": "yarn build && yarn test",
+    "test": "vitest"
+  }
+}
+"#
+            .to_string(),
+        );
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/yarn.lock"),
+            String::new(),
+        );
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/.pnp.cjs"),
+            String::new(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        expect_write(
+            &mut mock_fs,
+            PathBuf::from("/project/pnpm-workspace.yaml"),
+            "packages:\n  - 'packages/*'\n".to_string(),
+        );
+        expect_write(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            r#"{
+  "packageManager": "pnpm@9.4.0",
+  "scripts": {
+    "build": "tsc",
+    "ci": "pnpm build && pnpm test",
+    "test": "vitest"
+  }
+}
+"#
+            .to_string(),
+        );
+        mock_executor
+            .expect_output()
+            .withf(|program, args| program == "pnpm" && args == ["--version"])
+            .returning(|_, _| Ok("9.4.0\n".to_string()));
+        // old lockfile is removed only after a successful install
+        let mut seq = Sequence::new();
+        mock_executor
+            .expect_execute()
+            .withf(|program, args, _, _, _| program == "pnpm" && args == ["install"])
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _, _, _| Ok(()));
+        expect_remove(&mut mock_fs, "/project/yarn.lock", &mut seq);
+        expect_remove(&mut mock_fs, "/project/.pnp.cjs", &mut seq);
+
+        migrate(
+            &mock_executor,
+            &mock_fs,
+            Path::new("/project/packages/app"),
+            &Agent::Pnpm,
+            &Settings::default(),
+            false,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_plan_migration_pnpm_to_npm() {
+        let mut mock_fs = MockFilesystem::new();
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_output()
+            .returning(|_, _| Err(eyre!("not installed")));
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            r#"{"scripts":{"build":"tsc","start":"pnpm build && node ."}}"#.to_string(),
+        );
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/pnpm-lock.yaml"),
+            String::new(),
+        );
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/pnpm-workspace.yaml"),
+            "packages:\n  - apps/*\n".to_string(),
+        );
+        mock_fs
+            .expect_exists()
+            .with(eq(PathBuf::from("/project/node_modules")))
+            .returning(|_| true);
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert_eq!(
+            plan_migration(&mock_executor, &mock_fs, Path::new("/project"), &Agent::Npm).unwrap(),
+            vec![
+                FileChange::Remove(PathBuf::from("/project/pnpm-lock.yaml")),
+                FileChange::RemoveDir(PathBuf::from("/project/node_modules")),
+                FileChange::Remove(PathBuf::from("/project/pnpm-workspace.yaml")),
+                FileChange::Update {
+                    path: PathBuf::from("/project/package.json"),
+                    contents: r#"{"scripts":{"build":"tsc","start":"npm run build && node ."},"workspaces":["apps/*"]}"#
+                        .to_string()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrate_dry_run() {
+        let mut mock_fs = MockFilesystem::new();
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_output()
+            .returning(|_, _| Err(eyre!("not installed")));
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            r#"{"scripts":{"build":"tsc"}}"#.to_string(),
+        );
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/package-lock.json"),
+            String::new(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        // no writes or commands are expected
+        migrate(
+            &mock_executor,
+            &mock_fs,
+            Path::new("/project"),
+            &Agent::Bun,
+            &Settings::default(),
+            true,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_pnpm_workspace_yaml() {
+        assert_eq!(
+            pnpm_workspace_yaml(&["packages/*".to_string(), "it's".to_string()]),
+            "packages:\n  - 'packages/*'\n  - 'it''s'\n"
+        );
+    }
+}
@@ -1,4 +1,16 @@
 pub mod add;
+pub mod completions;
+pub mod doctor;
 pub mod install;
 pub mod install_ts_types;
+pub mod lint_scripts;
+pub mod migrate;
+pub mod outdated;
+pub mod pkg;
+pub mod remove;
 pub mod run;
+pub mod shim;
+pub mod sync_ts_types;
+pub mod types_policy;
+pub mod which;
+pub mod why;
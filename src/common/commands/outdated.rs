@@ -0,0 +1,530 @@
+use colored::Colorize;
+use eyre::{ContextCompat, Result};
+use std::path::Path;
+
+use crate::agent::Agent;
+use crate::execute::Executor;
+use crate::fs::{find_in_parents, Filesystem};
+use crate::http::HttpClient;
+use crate::manifest::PackageManifest;
+use crate::npmrc::Registries;
+use crate::semver::{Version, VersionReq};
+
+// groups checked when querying the registry, peer dependencies are installed by consumers
+const DEPENDENCY_GROUPS: &[&str] = &["dependencies", "devDependencies", "optionalDependencies"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutdatedPackage {
+    pub name: String,
+    /// installed version, None when package is missing in node_modules
+    pub current: Option<String>,
+    /// newest version matching the range from package.json
+    pub wanted: Option<String>,
+    pub latest: Option<String>,
+    pub dependency_type: String,
+}
+
+pub fn outdated(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    http_client: &dyn HttpClient,
+    agent: &Agent,
+    cwd: &Path,
+    json: bool,
+) -> Result<()> {
+    let package_json_path = find_in_parents(fs, cwd, "package.json").with_context(|| {
+        format!("Couldn't find package.json in the current directory: {cwd:?} or its parents.")
+    })?;
+    let manifest = PackageManifest::load(fs, &package_json_path)?;
+    let project_dir = package_json_path.parent().unwrap();
+    let registries = Registries::from_env(fs, project_dir);
+
+    let packages = find_outdated(
+        executor,
+        fs,
+        http_client,
+        agent,
+        project_dir,
+        &manifest,
+        &registries,
+    )?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&packages_to_json(&packages))?
+        );
+    } else if packages.is_empty() {
+        println!("All dependencies are up to date");
+    } else {
+        print_table(&packages);
+    }
+
+    Ok(())
+}
+
+/// Asks the package manager when it has a machine readable output (npm, pnpm and yarn v1), otherwise
+/// compares installed versions with registries configured in .npmrc
+pub fn find_outdated(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    http_client: &dyn HttpClient,
+    agent: &Agent,
+    project_dir: &Path,
+    manifest: &PackageManifest,
+    registries: &Registries,
+) -> Result<Vec<OutdatedPackage>> {
+    let args: Option<&[&str]> = match agent {
+        Agent::Npm => Some(&["outdated", "--json"]),
+        Agent::Pnpm => Some(&["outdated", "--format", "json"]),
+        Agent::Yarn if is_yarn_classic(executor) => Some(&["outdated", "--json"]),
+        _ => None,
+    };
+    let reported = args
+        .and_then(|args| executor.output(agent.program(), args).ok())
+        .and_then(|output| parse_agent_output(agent, &output));
+
+    let mut packages = match reported {
+        Some(packages) => packages
+            .into_iter()
+            .map(|mut package| {
+                if let Some(group) = dependency_type(manifest, &package.name) {
+                    package.dependency_type = group.to_string();
+                }
+                package
+            })
+            .collect(),
+        None => query_registry(fs, http_client, project_dir, manifest, registries)?,
+    };
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(packages)
+}
+
+pub(crate) fn is_yarn_classic(executor: &dyn Executor) -> bool {
+    executor
+        .output("yarn", &["--version"])
+        .is_ok_and(|version| version.trim().starts_with("1."))
+}
+
+/// Returns None when output can't be understood so the registry can be asked instead
+fn parse_agent_output(agent: &Agent, output: &str) -> Option<Vec<OutdatedPackage>> {
+    match agent {
+        Agent::Yarn => parse_yarn_classic_output(output),
+        _ => parse_npm_output(output),
+    }
+}
+
+/// npm and pnpm print objects keyed by package name
+fn parse_npm_output(output: &str) -> Option<Vec<OutdatedPackage>> {
+    if output.trim().is_empty() {
+        // pnpm prints nothing when everything is up to date
+        return Some(vec![]);
+    }
+    let report: serde_json::Value = serde_json::from_str(output).ok()?;
+    if report
+        .get("error")
+        .is_some_and(|error| error.get("code").is_some())
+    {
+        return None;
+    }
+
+    Some(
+        report
+            .as_object()?
+            .iter()
+            .map(|(name, info)| {
+                let field = |key: &str| info.get(key).and_then(|v| v.as_str()).map(String::from);
+                OutdatedPackage {
+                    name: name.clone(),
+                    current: field("current"),
+                    wanted: field("wanted"),
+                    latest: field("latest"),
+                    dependency_type: field("dependencyType")
+                        .or_else(|| field("type"))
+                        .unwrap_or_else(|| "dependencies".to_string()),
+                }
+            })
+            .collect(),
+    )
+}
+
+/// yarn v1 prints JSON lines, one of them is a table with package, current, wanted, latest and type columns
+fn parse_yarn_classic_output(output: &str) -> Option<Vec<OutdatedPackage>> {
+    let mut packages = vec![];
+    let mut understood = false;
+    for line in output.lines() {
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        understood = true;
+        if event.get("type").and_then(|t| t.as_str()) != Some("table") {
+            continue;
+        }
+        for row in event.get("data")?.get("body")?.as_array()? {
+            let cell = |i: usize| row.get(i).and_then(|v| v.as_str()).map(String::from);
+            packages.push(OutdatedPackage {
+                name: cell(0)?,
+                current: cell(1).filter(|current| current != "exotic"),
+                wanted: cell(2),
+                latest: cell(3),
+                dependency_type: cell(4).unwrap_or_else(|| "dependencies".to_string()),
+            });
+        }
+    }
+
+    understood.then_some(packages)
+}
+
+fn query_registry(
+    fs: &dyn Filesystem,
+    http_client: &dyn HttpClient,
+    project_dir: &Path,
+    manifest: &PackageManifest,
+    registries: &Registries,
+) -> Result<Vec<OutdatedPackage>> {
+    let mut packages = vec![];
+    for group in DEPENDENCY_GROUPS {
+        for (name, range) in manifest.dependency_group(group) {
+            // ranges like "workspace:*", git urls or tags can't be compared with the registry
+            let Some(req) = VersionReq::parse(range) else {
+                continue;
+            };
+            let Some((versions, latest)) = get_registry_versions(http_client, registries, name)?
+            else {
+                continue;
+            };
+            let current = installed_version(fs, project_dir, name);
+            let wanted = versions.iter().filter(|v| req.matches(v)).max();
+
+            let is_outdated = match (&current, wanted) {
+                (None, _) => true,
+                (Some(current), wanted) => {
+                    wanted.is_some_and(|wanted| wanted > current)
+                        || latest.as_ref().is_some_and(|latest| latest > current)
+                }
+            };
+            if is_outdated {
+                packages.push(OutdatedPackage {
+                    name: name.clone(),
+                    current: current.map(|v| v.to_string()),
+                    wanted: wanted.map(|v| v.to_string()),
+                    latest: latest.map(|v| v.to_string()),
+                    dependency_type: group.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(packages)
+}
+
+/// Stable versions of the package and its `latest` tag (newest stable version when tag is missing)
+fn get_registry_versions(
+    http_client: &dyn HttpClient,
+    registries: &Registries,
+    package: &str,
+) -> Result<Option<(Vec<Version>, Option<Version>)>> {
+    let registry = registries.registry_for(package);
+    let metadata = match http_client.request_json(&registries.package_url(package)) {
+        Ok(Some(metadata)) => metadata,
+        // packages missing in the public registry are local or private ones
+        Ok(None) if Registries::is_npm(registry) => return Ok(None),
+        Err(error) if Registries::is_npm(registry) => return Err(error),
+        // other registries usually need credentials which aren't sent
+        _ => {
+            println!(
+                "{}",
+                format!("Couldn't get versions of {package} from {registry}, skipping it").yellow()
+            );
+            return Ok(None);
+        }
+    };
+
+    let versions: Vec<Version> = metadata
+        .get("versions")
+        .and_then(|versions| versions.as_object())
+        .map(|versions| versions.keys().filter_map(|v| Version::parse(v)).collect())
+        .unwrap_or_default();
+    let versions: Vec<Version> = versions
+        .into_iter()
+        .filter(|v| !v.is_prerelease())
+        .collect();
+    let latest = metadata
+        .get("dist-tags")
+        .and_then(|tags| tags.get("latest"))
+        .and_then(|latest| latest.as_str())
+        .and_then(Version::parse)
+        .or_else(|| versions.iter().max().cloned());
+
+    Ok(Some((versions, latest)))
+}
+
+fn installed_version(fs: &dyn Filesystem, project_dir: &Path, package: &str) -> Option<Version> {
+    let path = find_in_parents(
+        fs,
+        project_dir,
+        &format!("node_modules/{package}/package.json"),
+    )?;
+
+    PackageManifest::load(fs, &path)
+        .ok()?
+        .version
+        .as_deref()
+        .and_then(Version::parse)
+}
+
+fn dependency_type(manifest: &PackageManifest, package: &str) -> Option<&'static str> {
+    DEPENDENCY_GROUPS
+        .iter()
+        .chain(&["peerDependencies"])
+        .find(|group| manifest.dependency_group(group).contains_key(package))
+        .copied()
+}
+
+fn print_table(packages: &[OutdatedPackage]) {
+    let missing = || "missing".to_string();
+    let rows: Vec<[String; 5]> = packages
+        .iter()
+        .map(|package| {
+            [
+                package.name.clone(),
+                package.current.clone().unwrap_or_else(missing),
+                package.wanted.clone().unwrap_or_else(missing),
+                package.latest.clone().unwrap_or_else(missing),
+                package.dependency_type.clone(),
+            ]
+        })
+        .collect();
+    let header = ["Package", "Current", "Wanted", "Latest", "Type"].map(String::from);
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|row| row[i].len())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let format_row = |row: &[String; 5]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(&header).bold());
+    for (package, row) in packages.iter().zip(&rows) {
+        let line = format_row(row);
+        // like npm: red when update matching the range is available, yellow when only major update is
+        if package.current.is_none() || package.current != package.wanted {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line.yellow());
+        }
+    }
+}
+
+fn packages_to_json(packages: &[OutdatedPackage]) -> serde_json::Value {
+    packages
+        .iter()
+        .map(|package| {
+            serde_json::json!({
+                "name": package.name,
+                "current": package.current,
+                "wanted": package.wanted,
+                "latest": package.latest,
+                "dependencyType": package.dependency_type,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::MockExecutor;
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+    use crate::http::{test_utils::expect_package_versions_in_registry, MockHttpClient};
+
+    use super::*;
+
+    fn package(name: &str, versions: [Option<&str>; 3], dependency_type: &str) -> OutdatedPackage {
+        let [current, wanted, latest] = versions.map(|v| v.map(String::from));
+        OutdatedPackage {
+            name: name.to_string(),
+            current,
+            wanted,
+            latest,
+            dependency_type: dependency_type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_npm_output() {
+        let output = r#"{
+            "zod": { "current": "3.21.0", "wanted": "3.23.8", "latest": "3.23.8", "location": "node_modules/zod" },
+            "react": { "wanted": "18.3.1", "latest": "19.0.0", "dependencyType": "devDependencies" }
+        }"#;
+
+        assert_eq!(
+            parse_npm_output(output),
+            Some(vec![
+                package(
+                    "zod",
+                    [Some("3.21.0"), Some("3.23.8"), Some("3.23.8")],
+                    "dependencies"
+                ),
+                package(
+                    "react",
+                    [None, Some("18.3.1"), Some("19.0.0")],
+                    "devDependencies"
+                ),
+            ])
+        );
+        assert_eq!(parse_npm_output(""), Some(vec![]));
+        assert_eq!(parse_npm_output("npm ERR! something"), None);
+        assert_eq!(
+            parse_npm_output(r#"{"error": {"code": "ENOENT", "summary": "no package.json"}}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_yarn_classic_output() {
+        let output = r#"{"type":"info","data":"Color legend"}
+{"type":"table","data":{"head":["Package","Current","Wanted","Latest","Package Type","URL"],"body":[["zod","3.21.0","3.23.8","3.23.8","devDependencies","https://zod.dev"]]}}"#;
+
+        assert_eq!(
+            parse_yarn_classic_output(output),
+            Some(vec![package(
+                "zod",
+                [Some("3.21.0"), Some("3.23.8"), Some("3.23.8")],
+                "devDependencies"
+            )])
+        );
+    }
+
+    #[test]
+    fn test_find_outdated_npm() {
+        let mock_fs = MockFilesystem::new();
+        let mock_http_client = MockHttpClient::new();
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_output()
+            .withf(|program, args| program == "npm" && args == ["outdated", "--json"])
+            .returning(|_, _| {
+                Ok(
+                    r#"{"zod": {"current": "3.21.0", "wanted": "3.23.8", "latest": "3.23.8"}}"#
+                        .to_string(),
+                )
+            });
+        let manifest =
+            PackageManifest::from_value(&serde_json::json!({ "devDependencies": { "zod": "^3" } }));
+
+        assert_eq!(
+            find_outdated(
+                &mock_executor,
+                &mock_fs,
+                &mock_http_client,
+                &Agent::Npm,
+                Path::new("/project"),
+                &manifest,
+                &Registries::default()
+            )
+            .unwrap(),
+            vec![package(
+                "zod",
+                [Some("3.21.0"), Some("3.23.8"), Some("3.23.8")],
+                "devDependencies"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_find_outdated_from_registry() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/node_modules/zod/package.json").to_owned(),
+            r#"{"version": "3.21.0"}"#.to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/node_modules/react/package.json").to_owned(),
+            r#"{"version": "18.3.1"}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        let mut mock_http_client = MockHttpClient::new();
+        expect_package_versions_in_registry(
+            &mut mock_http_client,
+            "zod",
+            Some(&["3.21.0", "3.23.8", "4.0.0-beta.1"]),
+        );
+        expect_package_versions_in_registry(
+            &mut mock_http_client,
+            "react",
+            Some(&["18.3.1", "19.0.0"]),
+        );
+        expect_package_versions_in_registry(&mut mock_http_client, "left-pad", Some(&["1.3.0"]));
+        let manifest = PackageManifest::from_value(&serde_json::json!({
+            "dependencies": { "zod": "^3.21.0", "react": "^18", "local": "workspace:*" },
+            "devDependencies": { "left-pad": "^1" }
+        }));
+
+        assert_eq!(
+            find_outdated(
+                &MockExecutor::new(),
+                &mock_fs,
+                &mock_http_client,
+                &Agent::Bun,
+                Path::new("/project"),
+                &manifest,
+                &Registries::default()
+            )
+            .unwrap(),
+            vec![
+                package(
+                    "left-pad",
+                    [None, Some("1.3.0"), Some("1.3.0")],
+                    "devDependencies"
+                ),
+                package(
+                    "react",
+                    [Some("18.3.1"), Some("18.3.1"), Some("19.0.0")],
+                    "dependencies"
+                ),
+                package(
+                    "zod",
+                    [Some("3.21.0"), Some("3.23.8"), Some("3.23.8")],
+                    "dependencies"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_registry_versions_from_private_registry() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/.npmrc").to_owned(),
+            "@acme:registry=https://npm.acme.dev/".to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        let registries = Registries::load(&mock_fs, Path::new("/project"), None);
+        let mut mock_http_client = MockHttpClient::new();
+        mock_http_client
+            .expect_request_json()
+            .withf(|url| url == "https://npm.acme.dev/@acme/ui")
+            .returning(|_| Ok(None));
+
+        // skipped with a warning instead of asking the public registry
+        assert_eq!(
+            get_registry_versions(&mock_http_client, &registries, "@acme/ui").unwrap(),
+            None
+        );
+    }
+}
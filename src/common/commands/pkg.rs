@@ -0,0 +1,339 @@
+use colored::Colorize;
+use eyre::{eyre, Context, ContextCompat, Result};
+use std::path::{Path, PathBuf};
+
+use crate::fs::{find_in_parents, Filesystem};
+use crate::manifest_editor::ManifestEditor;
+
+/// Prints values under the keys. A single string is printed as is, anything else as JSON
+pub fn pkg_get(fs: &dyn Filesystem, cwd: &Path, keys: &[String]) -> Result<()> {
+    let editor = ManifestEditor::load(fs, &find_package_json(fs, cwd)?)?;
+
+    match get_values(&editor, keys)? {
+        serde_json::Value::String(value) => println!("{value}"),
+        value => println!("{}", serde_json::to_string_pretty(&value)?),
+    }
+
+    Ok(())
+}
+
+/// Sets `key=value` assignments. Values are strings unless `json` is set. With `dry_run` only prints the
+/// changes
+pub fn pkg_set(
+    fs: &dyn Filesystem,
+    cwd: &Path,
+    assignments: &[String],
+    json: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let path = find_package_json(fs, cwd)?;
+    let mut editor = ManifestEditor::load(fs, &path)?;
+    let original = editor.to_string();
+
+    for assignment in assignments {
+        let (key, value) = assignment
+            .split_once('=')
+            .with_context(|| format!("Expected key=value, got: {assignment}"))?;
+        let value = if json {
+            serde_json::from_str(value)
+                .with_context(|| format!("Invalid JSON value of {key}: {value}"))?
+        } else {
+            serde_json::Value::String(value.to_string())
+        };
+        let keys = parse_key_path(key)?;
+
+        editor.set(&as_strs(&keys), value)?;
+    }
+
+    save(fs, &path, &original, &editor, dry_run)
+}
+
+/// Removes keys, missing ones are ignored. With `dry_run` only prints the changes
+pub fn pkg_delete(fs: &dyn Filesystem, cwd: &Path, keys: &[String], dry_run: bool) -> Result<()> {
+    let path = find_package_json(fs, cwd)?;
+    let mut editor = ManifestEditor::load(fs, &path)?;
+    let original = editor.to_string();
+
+    let mut changed = false;
+    for key in keys {
+        changed |= editor.delete(&as_strs(&parse_key_path(key)?)).is_some();
+    }
+
+    if changed {
+        save(fs, &path, &original, &editor, dry_run)?;
+    }
+
+    Ok(())
+}
+
+fn save(
+    fs: &dyn Filesystem,
+    path: &Path,
+    original: &str,
+    editor: &ManifestEditor,
+    dry_run: bool,
+) -> Result<()> {
+    if !dry_run {
+        return editor.save(fs, path);
+    }
+
+    println!("Would update {}:", path.display());
+    let contents = editor.to_string();
+    let (removed, added) = changed_lines(original, &contents);
+    for line in removed {
+        println!("{}", format!("- {line}").red());
+    }
+    for line in added {
+        println!("{}", format!("+ {line}").green());
+    }
+
+    Ok(())
+}
+
+/// Lines between the common beginning and end of both texts
+fn changed_lines<'a>(before: &'a str, after: &'a str) -> (Vec<&'a str>, Vec<&'a str>) {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    (
+        before[prefix..before.len() - suffix].to_vec(),
+        after[prefix..after.len() - suffix].to_vec(),
+    )
+}
+
+/// Whole package.json without keys, single value for one key and object of found values for more keys
+fn get_values(editor: &ManifestEditor, keys: &[String]) -> Result<serde_json::Value> {
+    let get = |key: &str| -> Result<Option<serde_json::Value>> {
+        Ok(editor.get(&as_strs(&parse_key_path(key)?)).cloned())
+    };
+
+    match keys {
+        [] => Ok(editor.get(&[]).cloned().unwrap_or_default()),
+        [key] => get(key)?.ok_or_else(|| eyre!("{key} is not set in package.json")),
+        keys => {
+            let mut values = serde_json::Map::new();
+            for key in keys {
+                if let Some(value) = get(key)? {
+                    values.insert(key.clone(), value);
+                }
+            }
+            Ok(serde_json::Value::Object(values))
+        }
+    }
+}
+
+/// Splits `scripts.build` or `exports["./utils"]` into keys
+fn parse_key_path(path: &str) -> Result<Vec<String>> {
+    let invalid = || eyre!("Invalid key: {path}");
+    let mut keys = vec![];
+    let mut rest = path;
+
+    while !rest.is_empty() {
+        if let Some(bracketed) = rest.strip_prefix('[') {
+            let end = bracketed.find(']').ok_or_else(invalid)?;
+            let key = &bracketed[..end];
+            let key = ['"', '\'']
+                .iter()
+                .find_map(|quote| key.strip_prefix(*quote)?.strip_suffix(*quote))
+                .unwrap_or(key);
+            keys.push(key.to_string());
+            rest = &bracketed[end + 1..];
+            rest = rest.strip_prefix('.').unwrap_or(rest);
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            keys.push(rest[..end].to_string());
+            rest = &rest[end..];
+            rest = rest.strip_prefix('.').unwrap_or(rest);
+        }
+    }
+
+    if keys.is_empty() || path.ends_with('.') {
+        return Err(invalid());
+    }
+
+    Ok(keys)
+}
+
+fn find_package_json(fs: &dyn Filesystem, cwd: &Path) -> Result<PathBuf> {
+    find_in_parents(fs, cwd, "package.json").with_context(|| {
+        format!("Couldn't find package.json in the current directory: {cwd:?} or its parents.")
+    })
+}
+
+fn as_strs(keys: &[String]) -> Vec<&str> {
+    keys.iter().map(String::as_str).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::fs::{
+        test_utils::{expect_file, expect_write},
+        MockFilesystem,
+    };
+
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_key_path() {
+        assert_eq!(
+            parse_key_path("scripts.build").unwrap(),
+            ["scripts", "build"]
+        );
+        assert_eq!(
+            parse_key_path(r#"exports["./utils"].import"#).unwrap(),
+            ["exports", "./utils", "import"]
+        );
+        assert_eq!(parse_key_path("a['b.c']").unwrap(), ["a", "b.c"]);
+        assert!(parse_key_path("").is_err());
+        assert!(parse_key_path("scripts.").is_err());
+        assert!(parse_key_path("a..b").is_err());
+        assert!(parse_key_path("a[b").is_err());
+    }
+
+    #[test]
+    fn test_get_values() {
+        let editor = ManifestEditor::parse(
+            r#"{"name": "a", "scripts": {"build": "tsc"}, "engines": {"node": ">=18"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            get_values(&editor, &strings(&["scripts.build"])).unwrap(),
+            json!("tsc")
+        );
+        assert_eq!(
+            get_values(&editor, &strings(&["name", "engines.node", "version"])).unwrap(),
+            json!({ "name": "a", "engines.node": ">=18" })
+        );
+        assert!(get_values(&editor, &strings(&["version"])).is_err());
+    }
+
+    #[test]
+    fn test_pkg_set() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            "{\n  \"name\": \"a\"\n}\n".to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        expect_write(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            "{\n  \"name\": \"a\",\n  \"scripts\": {\n    \"build\": \"tsc -p .\"\n  },\n  \"engines\": {\n    \"node\": \">=18\"\n  }\n}\n"
+                .to_owned(),
+        );
+
+        pkg_set(
+            &mock_fs,
+            Path::new("/project/src"),
+            &strings(&["scripts.build=tsc -p .", "engines.node=>=18"]),
+            false,
+            false,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_pkg_set_json() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"name": "a"}"#.to_owned(),
+        );
+        expect_write(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            r#"{"name": "a", "private": true, "files": ["dist"]}"#.to_owned(),
+        );
+
+        let cwd = Path::new("/project");
+        pkg_set(
+            &mock_fs,
+            cwd,
+            &strings(&["private=true", r#"files=["dist"]"#]),
+            true,
+            false,
+        )
+        .unwrap();
+        assert!(pkg_set(&mock_fs, cwd, &strings(&["private=yes"]), true, false).is_err());
+        assert!(pkg_set(&mock_fs, cwd, &strings(&["private"]), false, false).is_err());
+    }
+
+    #[test]
+    fn test_pkg_set_dry_run() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"name": "a"}"#.to_owned(),
+        );
+
+        // no writes are expected
+        pkg_set(
+            &mock_fs,
+            Path::new("/project"),
+            &strings(&["name=b"]),
+            false,
+            true,
+        )
+        .unwrap();
+        pkg_delete(&mock_fs, Path::new("/project"), &strings(&["name"]), true).unwrap();
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        assert_eq!(
+            changed_lines(
+                "{\n  \"a\": 1,\n  \"b\": 2\n}",
+                "{\n  \"a\": 3,\n  \"b\": 2\n}"
+            ),
+            (vec!["  \"a\": 1,"], vec!["  \"a\": 3,"])
+        );
+        assert_eq!(changed_lines("{}", "{}"), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_pkg_delete() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"name": "a", "scripts": {"build": "tsc", "test": "mocha"}}"#.to_owned(),
+        );
+        expect_write(
+            &mut mock_fs,
+            PathBuf::from("/project/package.json"),
+            r#"{"name": "a", "scripts": {"test": "mocha"}}"#.to_owned(),
+        );
+
+        pkg_delete(
+            &mock_fs,
+            Path::new("/project"),
+            &strings(&["scripts.build", "version"]),
+            false,
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{agent::Agent, config::Settings, execute::Executor};
+use eyre::{eyre, Result};
+
+pub fn remove(
+    executor: &dyn Executor,
+    agent: &Agent,
+    workspace_root: bool,
+    packages: &[String],
+    silence_stdout: bool,
+    settings: &Settings,
+) -> Result<()> {
+    let mut args = vec![match agent {
+        Agent::Npm => "uninstall",
+        Agent::Yarn | Agent::Pnpm | Agent::Bun => "remove",
+    }];
+
+    if workspace_root {
+        match agent {
+            Agent::Npm => {} //npm doesn't require workspace_root flag
+            Agent::Yarn => args.push("-W"),
+            Agent::Pnpm => args.push("-w"),
+            Agent::Bun => return Err(eyre!("Bun doesn't support workspace_root flag")),
+        }
+    }
+    args.extend(packages.iter().map(|s| s.as_str()));
+
+    executor.execute(
+        agent.program(),
+        &args,
+        None,
+        settings.verbose,
+        silence_stdout,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::{expect_execute_once, MockExecutor};
+    use crate::utils::vec_of_strings;
+
+    use super::*;
+
+    #[test]
+    fn test_remove_npm() {
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "npm",
+            vec_of_strings!("uninstall", "packageA"),
+            None,
+            true,
+            false,
+        );
+
+        let result = remove(
+            &mock_executor,
+            &Agent::Npm,
+            true,
+            &["packageA"].map(String::from),
+            false,
+            &Settings::default(),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_remove_pnpm_workspace() {
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "pnpm",
+            vec_of_strings!("remove", "-w", "packageA", "packageB"),
+            None,
+            true,
+            false,
+        );
+
+        let result = remove(
+            &mock_executor,
+            &Agent::Pnpm,
+            true,
+            &["packageA", "packageB"].map(String::from),
+            false,
+            &Settings::default(),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_remove_bun_workspace() {
+        let mock_executor = MockExecutor::new();
+
+        let result = remove(
+            &mock_executor,
+            &Agent::Bun,
+            true,
+            &["packageA"].map(String::from),
+            false,
+            &Settings::default(),
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -1,8 +1,18 @@
 use colored::Colorize;
-use eyre::{ContextCompat, Result};
+use eyre::{eyre, ContextCompat, Result};
 use std::path::Path;
 
-use crate::{agent::Agent, execute::Executor, fs::find_in_parents, fs::Filesystem};
+use crate::{
+    agent::Agent,
+    config::Settings,
+    execute::Executor,
+    fs::find_in_parents,
+    fs::Filesystem,
+    manifest::PackageManifest,
+    node_version::{find_node_requirements, resolve_node_bin, VersionManagers},
+};
+
+use super::shim::write_shims;
 
 pub fn run(
     executor: &dyn Executor,
@@ -11,34 +21,41 @@ pub fn run(
     task: &str,
     cwd: &Path,
     extra_args: Option<&[&str]>,
+    settings: &Settings,
 ) -> Result<()> {
-    // for alternative runtimes, just proxy the call
-    if agent == &Agent::Bun {
-        let mut bun_args = vec!["run", task];
-
-        // append extra args if any
-        if let Some(extra_args) = extra_args {
-            bun_args.extend_from_slice(extra_args);
-        }
-
-        return executor.execute("bun", &bun_args, None, true, false);
+    if is_script_file(task) && fs.exists(&cwd.join(task)) {
+        return run_file(executor, fs, agent, task, cwd, extra_args, settings);
     }
 
     let package_json_path = find_in_parents(fs, cwd, "package.json").with_context(|| {
         format!("Couldn't find package.json in the current directory: {cwd:?} or its parents.")
     })?;
-    let package_json = load_package_json(fs, &package_json_path)
-        .with_context(|| format!("Couldn't parse package.json: {package_json_path:?}"))?;
-
-    let bin_path = construct_path_env(package_json_path.parent().unwrap());
-
-    if let Some(mut script) = load_script(&package_json, task) {
+    let manifest = PackageManifest::load(fs, &package_json_path)?;
+    let project_dir = package_json_path.parent().unwrap();
+    // shims are written to node_modules, there is nothing to run before dependencies are installed anyway
+    let shims_dir = if settings.shims && fs.exists(&project_dir.join("node_modules")) {
+        Some(write_shims(fs, project_dir, agent)?)
+    } else {
+        None
+    };
+
+    let bin_path = construct_project_path_env(
+        executor,
+        fs,
+        project_dir,
+        Some(&manifest),
+        shims_dir.as_deref(),
+    );
+
+    if let Some(mut script) = load_script(&manifest, task) {
         if let Some(extra_args) = extra_args {
             script += " ";
             script += &extra_args.join(" ")
         }
 
-        println!("{}", format!("$ {script}").dimmed());
+        if settings.verbose {
+            println!("{}", format!("$ {script}").dimmed());
+        }
 
         executor.execute(
             "sh",
@@ -47,6 +64,18 @@ pub fn run(
             false, // do not print command as it's quite odd to see "sh -c <script>"
             false, // do not silence output
         )
+    } else if agent == &Agent::Bun && is_local_binary(fs, cwd, task) {
+        // in bun projects binaries are executed by bun runtime even if they were written for node
+        let mut bun_args = vec!["run", "--bun", task];
+        bun_args.extend_from_slice(extra_args.unwrap_or_default());
+
+        executor.execute(
+            "bun",
+            &bun_args,
+            Some(bin_path),
+            settings.verbose, // print out command being executed
+            false,            // do not silence output
+        )
     } else {
         let program = task;
 
@@ -54,29 +83,131 @@ pub fn run(
             program,
             extra_args.unwrap_or_default(),
             Some(bin_path),
-            true,  // print out command being executed
-            false, // do not silence output
+            settings.verbose, // print out command being executed
+            false,            // do not silence output
         )
     }
 }
 
-fn load_package_json(fs: &dyn Filesystem, path: &Path) -> Option<serde_json::Value> {
-    fs.read_to_string(path)
-        .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
+fn is_local_binary(fs: &dyn Filesystem, cwd: &Path, program: &str) -> bool {
+    find_in_parents(fs, cwd, &format!("node_modules/.bin/{program}")).is_some()
+}
+
+const JS_EXTENSIONS: &[&str] = &["js", "mjs", "cjs"];
+const TS_EXTENSIONS: &[&str] = &["ts", "mts", "cts", "tsx"];
+
+fn is_script_file(task: &str) -> bool {
+    file_extension(task).is_some_and(|extension| {
+        JS_EXTENSIONS.contains(&extension) || TS_EXTENSIONS.contains(&extension)
+    })
+}
+
+fn is_ts_file(file: &str) -> bool {
+    file_extension(file).is_some_and(|extension| TS_EXTENSIONS.contains(&extension))
+}
+
+fn file_extension(file: &str) -> Option<&str> {
+    Path::new(file)
+        .extension()
+        .and_then(|extension| extension.to_str())
 }
 
-fn load_script(package_json: &serde_json::Value, script_name: &str) -> Option<String> {
-    package_json
-        .get("scripts")
-        .and_then(|scripts| scripts.get(script_name))
-        .and_then(|script| script.as_str())
-        .map(|s| s.to_string())
+fn run_file(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    agent: &Agent,
+    file: &str,
+    cwd: &Path,
+    extra_args: Option<&[&str]>,
+    settings: &Settings,
+) -> Result<()> {
+    let (runtime, mut args) = find_file_runtime(executor, fs, agent, cwd, file)?;
+    args.push(file);
+    args.extend_from_slice(extra_args.unwrap_or_default());
+
+    let package_json_path = find_in_parents(fs, cwd, "package.json");
+    let manifest = package_json_path
+        .as_ref()
+        .and_then(|path| PackageManifest::load(fs, path).ok());
+    let project_dir = package_json_path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .unwrap_or(cwd);
+    let bin_path = construct_project_path_env(executor, fs, project_dir, manifest.as_ref(), None);
+
+    executor.execute(runtime, &args, Some(bin_path), settings.verbose, false)
+}
+
+/// Program (with its args) executing the file. Bun projects use bun for everything, otherwise JS is run by node
+/// and TS by the first available of: local tsx or ts-node, bun, deno
+fn find_file_runtime(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    agent: &Agent,
+    cwd: &Path,
+    file: &str,
+) -> Result<(&'static str, Vec<&'static str>)> {
+    if agent == &Agent::Bun {
+        return Ok(("bun", vec![]));
+    }
+    if !is_ts_file(file) {
+        return Ok(("node", vec![]));
+    }
+
+    for local_runtime in ["tsx", "ts-node"] {
+        if is_local_binary(fs, cwd, local_runtime) {
+            return Ok((local_runtime, vec![]));
+        }
+    }
+    if executor.which("bun").is_some() {
+        return Ok(("bun", vec![]));
+    }
+    if executor.which("deno").is_some() {
+        // deno denies access to files, env and network by default, unlike other runtimes
+        println!(
+            "{}",
+            format!("Running {file} with deno with all permissions (--allow-all)").yellow()
+        );
+        return Ok(("deno", vec!["run", "--allow-all"]));
+    }
+
+    Err(eyre!(
+        "Couldn't find a runtime for TypeScript file: {file}. Add one with `ny add -D tsx`"
+    ))
 }
 
-fn construct_path_env(dir: &Path) -> String {
+pub(crate) fn load_script(manifest: &PackageManifest, script_name: &str) -> Option<String> {
+    manifest.scripts.get(script_name).cloned()
+}
+
+// PATH with local binaries of the project and Node.js matching its requirements
+fn construct_project_path_env(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    project_dir: &Path,
+    manifest: Option<&PackageManifest>,
+    shims_dir: Option<&Path>,
+) -> String {
+    let node_requirements = find_node_requirements(fs, project_dir, manifest);
+    let node_bin = resolve_node_bin(
+        executor,
+        fs,
+        &VersionManagers::from_env(),
+        &node_requirements,
+    );
+
+    construct_path_env(project_dir, node_bin.as_deref(), shims_dir)
+}
+
+// node_bin is a directory of Node.js installation matching project's requirements, when active one doesn't.
+// shims_dir goes first so shims take precedence over package managers installed locally
+fn construct_path_env(dir: &Path, node_bin: Option<&Path>, shims_dir: Option<&Path>) -> String {
     // @note: we avoid for checking if <dir>/node_modules/.bin even exists because OS will just handle such cases automatically for us.
     let mut path_env = String::new();
+    if let Some(shims_dir) = shims_dir {
+        path_env += shims_dir.to_str().unwrap();
+        path_env += ":";
+    }
 
     let mut current_dir = dir;
     loop {
@@ -89,6 +220,10 @@ fn construct_path_env(dir: &Path) -> String {
             break;
         }
     }
+    if let Some(node_bin) = node_bin {
+        path_env += node_bin.to_str().unwrap();
+        path_env += ":";
+    }
 
     path_env
 }
@@ -104,26 +239,42 @@ mod tests {
 
     #[test]
     fn test_load_script() {
-        let package_json = serde_json::json!({
+        let manifest = PackageManifest::from_value(&serde_json::json!({
             "scripts": {
                 "test": "echo \"test\""
             }
-        });
+        }));
 
-        let result = load_script(&package_json, "test");
+        let result = load_script(&manifest, "test");
         assert!(result.is_some());
         assert_eq!(result.unwrap(), "echo \"test\"");
 
-        let result = load_script(&package_json, "test2");
+        let result = load_script(&manifest, "test2");
         assert!(result.is_none());
     }
 
     #[test]
     fn test_construct_path_env() {
         assert_eq!(
-            construct_path_env(Path::new("/project/nested")),
+            construct_path_env(Path::new("/project/nested"), None, None),
             "/project/nested/node_modules/.bin:/project/node_modules/.bin:/node_modules/.bin:"
         );
+        assert_eq!(
+            construct_path_env(
+                Path::new("/project"),
+                Some(Path::new("/home/.nvm/versions/node/v18.19.0/bin")),
+                None
+            ),
+            "/project/node_modules/.bin:/node_modules/.bin:/home/.nvm/versions/node/v18.19.0/bin:"
+        );
+        assert_eq!(
+            construct_path_env(
+                Path::new("/project"),
+                None,
+                Some(Path::new("/project/node_modules/.cache/ny/shims"))
+            ),
+            "/project/node_modules/.cache/ny/shims:/project/node_modules/.bin:/node_modules/.bin:"
+        );
     }
 
     #[test]
@@ -143,6 +294,7 @@ mod tests {
             Path::new("/project/package.json").to_owned(),
             r#"{"scripts": {"test": "mocha \"*.ts\""}}"#.to_owned(),
         );
+        mock_fs.expect_exists().returning(|_| false);
 
         let result = run(
             &mock_executor,
@@ -151,11 +303,47 @@ mod tests {
             "test",
             Path::new("/project"),
             Some(&["--no-timeout", "--bail"]),
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn command_run_skips_shims_without_node_modules() {
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "sh",
+            vec_of_strings!("-c", "mocha"),
+            Some("/project/node_modules/.bin:/node_modules/.bin:".to_string()),
+            false,
+            false,
+        );
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"scripts": {"test": "mocha"}}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        // no shims are written
+        run(
+            &mock_executor,
+            &mock_fs,
+            &Agent::Npm,
+            "test",
+            Path::new("/project"),
+            None,
+            &Settings {
+                shims: true,
+                ..Settings::default()
+            },
+        )
+        .unwrap();
+    }
+
     #[test]
     fn command_run_from_node_modules() {
         let mut mock_executor = MockExecutor::new();
@@ -173,6 +361,7 @@ mod tests {
             Path::new("/project/package.json").to_owned(),
             r#"{"scripts": {}}"#.to_owned(),
         );
+        mock_fs.expect_exists().returning(|_| false);
 
         let result = run(
             &mock_executor,
@@ -181,6 +370,7 @@ mod tests {
             "mocha",
             Path::new("/project"),
             Some(&["--help"]),
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
@@ -188,16 +378,75 @@ mod tests {
 
     #[test]
     fn command_run_by_bun() {
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "sh",
+            vec_of_strings!("-c", "bun test --no-timeout"),
+            Some("/project/node_modules/.bin:/node_modules/.bin:".to_string()),
+            false,
+            false,
+        );
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"scripts": {"test": "bun test"}}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        let result = run(
+            &mock_executor,
+            &mock_fs,
+            &Agent::Bun,
+            "test",
+            Path::new("/project"),
+            Some(&["--no-timeout"]),
+            &Settings::default(),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn command_run_binary_by_bun() {
         let mut mock_executor = MockExecutor::new();
         expect_execute_once(
             &mut mock_executor,
             "bun",
-            vec_of_strings!("run", "test", "--no-timeout"),
-            None,
+            vec_of_strings!("run", "--bun", "vite", "build"),
+            Some("/project/node_modules/.bin:/node_modules/.bin:".to_string()),
             true,
             false,
         );
-        let mock_fs = MockFilesystem::new();
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"scripts": {}}"#.to_owned(),
+        );
+        mock_fs
+            .expect_exists()
+            .returning(|path| path == Path::new("/project/node_modules/.bin/vite"));
+
+        let result = run(
+            &mock_executor,
+            &mock_fs,
+            &Agent::Bun,
+            "vite",
+            Path::new("/project"),
+            Some(&["build"]),
+            &Settings::default(),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn command_run_by_bun_without_package_json() {
+        let mock_executor = MockExecutor::new();
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|_| false);
 
         let result = run(
             &mock_executor,
@@ -205,9 +454,92 @@ mod tests {
             &Agent::Bun,
             "test",
             Path::new("/project"),
-            Some(&["--no-timeout"]),
+            None,
+            &Settings::default(),
+        );
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Couldn't find package.json"));
+    }
+
+    #[test]
+    fn command_run_js_file() {
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "node",
+            vec_of_strings!("scripts/seed.mjs", "--force"),
+            Some("/project/node_modules/.bin:/node_modules/.bin:".to_string()),
+            true,
+            false,
+        );
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{}"#.to_owned(),
+        );
+        mock_fs
+            .expect_exists()
+            .returning(|path| path == Path::new("/project/scripts/seed.mjs"));
+
+        let result = run(
+            &mock_executor,
+            &mock_fs,
+            &Agent::Pnpm,
+            "scripts/seed.mjs",
+            Path::new("/project"),
+            Some(&["--force"]),
+            &Settings::default(),
         );
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_find_file_runtime() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs
+            .expect_exists()
+            .returning(|path| path == Path::new("/project/node_modules/.bin/ts-node"));
+        let mut mock_executor = MockExecutor::new();
+        mock_executor.expect_which().returning(|_| None);
+        let cwd = Path::new("/project/src");
+
+        let runtime = |agent, file| find_file_runtime(&mock_executor, &mock_fs, &agent, cwd, file);
+        assert_eq!(runtime(Agent::Npm, "seed.cjs").unwrap(), ("node", vec![]));
+        assert_eq!(runtime(Agent::Npm, "seed.ts").unwrap(), ("ts-node", vec![]));
+        assert_eq!(runtime(Agent::Bun, "seed.tsx").unwrap(), ("bun", vec![]));
+    }
+
+    #[test]
+    fn test_is_ts_file() {
+        assert!(is_ts_file("scripts/seed.mts"));
+        assert!(!is_ts_file("scripts/foots"));
+        assert!(!is_ts_file("seed.js"));
+    }
+
+    #[test]
+    fn test_find_file_runtime_fallbacks() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|_| false);
+        let mut mock_executor = MockExecutor::new();
+        mock_executor.expect_which().returning(|program| {
+            (program == "deno").then(|| std::path::PathBuf::from("/usr/bin/deno"))
+        });
+        let cwd = Path::new("/project");
+
+        assert_eq!(
+            find_file_runtime(&mock_executor, &mock_fs, &Agent::Yarn, cwd, "seed.mts").unwrap(),
+            ("deno", vec!["run", "--allow-all"])
+        );
+
+        let mut mock_executor = MockExecutor::new();
+        mock_executor.expect_which().returning(|_| None);
+        assert!(
+            find_file_runtime(&mock_executor, &mock_fs, &Agent::Yarn, cwd, "seed.mts").is_err()
+        );
+    }
 }
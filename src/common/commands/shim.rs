@@ -0,0 +1,225 @@
+use clap::ValueEnum;
+use eyre::{eyre, Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    agent::Agent,
+    config::Settings,
+    execute::Executor,
+    fs::{find_in_parents, Filesystem},
+    invocations::{find_invocations, InvocationKind},
+    manifest::PackageManifest,
+};
+
+use super::{install::install, run::run};
+
+const SHIMS_DIR: &str = "node_modules/.cache/ny/shims";
+
+/// Writes executables named after package managers other than `agent` which forward their calls to
+/// `ny __shim`. Returns the directory containing them
+pub(crate) fn write_shims(
+    fs: &dyn Filesystem,
+    project_dir: &Path,
+    agent: &Agent,
+) -> Result<PathBuf> {
+    let dir = project_dir.join(SHIMS_DIR);
+    let ny = std::env::current_exe()
+        .ok()
+        .and_then(|path| path.to_str().map(str::to_string))
+        .unwrap_or_else(|| "ny".to_string());
+
+    for other in Agent::value_variants()
+        .iter()
+        .filter(|other| *other != agent)
+    {
+        let program = other.program();
+        let path = dir.join(program);
+        let contents = format!("#!/bin/sh\nexec {} __shim {program} \"$@\"\n", quote(&ny));
+        // don't touch shims on every run
+        if fs.read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+            fs.write_executable(&path, &contents)
+                .with_context(|| format!("Couldn't write shim: {path:?}"))?;
+        }
+    }
+
+    Ok(dir)
+}
+
+/// Handles a package manager call intercepted by a shim. Running scripts and installing dependencies goes
+/// through ny, anything else is passed to the real package manager found in `path_env`
+#[allow(clippy::too_many_arguments)]
+pub fn shim(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    agent: &Agent,
+    cwd: &Path,
+    program: &str,
+    args: &[String],
+    settings: &Settings,
+    path_env: &str,
+) -> Result<()> {
+    let scripts: Vec<String> = find_in_parents(fs, cwd, "package.json")
+        .and_then(|path| PackageManifest::load(fs, &path).ok())
+        .map(|manifest| manifest.scripts.into_keys().collect())
+        .unwrap_or_default();
+    let command = std::iter::once(program.to_string())
+        .chain(args.iter().map(|arg| quote(arg)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    // options npm takes for itself can only be handled by npm
+    let invocation = find_invocations(&command, &scripts)
+        .into_iter()
+        .find(|invocation| {
+            invocation.range == (0..command.len()) && invocation.agent_args.is_empty()
+        });
+
+    match invocation.map(|invocation| (invocation.kind, invocation.args)) {
+        Some((InvocationKind::Run(task), task_args)) => {
+            let task_args: Vec<&str> = task_args.iter().map(String::as_str).collect();
+            run(executor, fs, agent, &task, cwd, Some(&task_args), settings)
+        }
+        Some((InvocationKind::Install { frozen: false }, _)) => install(executor, agent, settings),
+        _ => {
+            let real_program = find_real_program(fs, program, path_env)
+                .ok_or_else(|| eyre!("Couldn't find {program} in PATH"))?;
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+            executor.execute(real_program.to_str().unwrap(), &args, None, false, false)
+        }
+    }
+}
+
+fn find_real_program(fs: &dyn Filesystem, program: &str, path_env: &str) -> Option<PathBuf> {
+    path_env
+        .split(':')
+        .map(Path::new)
+        .filter(|dir| !dir.as_os_str().is_empty() && !dir.ends_with(SHIMS_DIR))
+        .map(|dir| dir.join(program))
+        .find(|path| fs.exists(path))
+}
+
+/// Quotes the argument so it stays a single word when passed through `sh -c`
+fn quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_=./:@%+,".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execute::{expect_execute_once, MockExecutor};
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+    use crate::utils::vec_of_strings;
+
+    use super::*;
+
+    #[test]
+    fn test_write_shims() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs
+            .expect_read_to_string()
+            .returning(|_| Err(eyre!("missing")));
+        mock_fs
+            .expect_write_executable()
+            .withf(|path, contents| {
+                ["npm", "yarn", "bun"]
+                    .iter()
+                    .any(|program| path == Path::new(SHIMS_DIR).join(program).as_path())
+                    && contents.contains("__shim")
+            })
+            .times(3)
+            .returning(|_, _| Ok(()));
+
+        let dir = write_shims(&mock_fs, Path::new(""), &Agent::Pnpm).unwrap();
+
+        assert_eq!(dir, Path::new(SHIMS_DIR));
+    }
+
+    #[test]
+    fn test_shim_runs_script() {
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "sh",
+            vec_of_strings!("-c", "tsc --outDir 'dist dir'"),
+            Some("/project/node_modules/.bin:/node_modules/.bin:".to_string()),
+            false,
+            false,
+        );
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"scripts": {"build": "tsc"}}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        shim(
+            &mock_executor,
+            &mock_fs,
+            &Agent::Pnpm,
+            Path::new("/project"),
+            "npm",
+            &["run", "build", "--", "--outDir", "dist dir"].map(String::from),
+            &Settings::default(),
+            "/usr/bin",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_shim_passes_through_other_commands() {
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "/usr/bin/yarn",
+            vec_of_strings!("publish", "--tag", "next"),
+            None,
+            false,
+            false,
+        );
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"scripts": {"build": "tsc"}}"#.to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/node_modules/.cache/ny/shims/yarn").to_owned(),
+            String::new(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/usr/bin/yarn").to_owned(),
+            String::new(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        shim(
+            &mock_executor,
+            &mock_fs,
+            &Agent::Pnpm,
+            Path::new("/project"),
+            "yarn",
+            &["publish", "--tag", "next"].map(String::from),
+            &Settings::default(),
+            "/project/node_modules/.cache/ny/shims:/usr/local/bin:/usr/bin",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("--outDir=dist/"), "--outDir=dist/");
+        assert_eq!(quote("a b"), "'a b'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote(""), "''");
+    }
+}
@@ -0,0 +1,221 @@
+use colored::Colorize;
+use eyre::{ContextCompat, Result};
+use std::path::Path;
+
+use crate::agent::Agent;
+use crate::config::Settings;
+use crate::execute::Executor;
+use crate::fs::{find_in_parents, Filesystem};
+use crate::http::HttpClient;
+use crate::manifest::PackageManifest;
+
+use super::install_ts_types::{install_ts_types, types_package_name_to_package_name};
+use super::remove::remove;
+
+// @types packages describing runtime environment rather than a particular dependency
+const AMBIENT_TYPES_PACKAGES: &[&str] = &["@types/node", "@types/bun", "bun-types", "@types/web"];
+
+#[allow(clippy::too_many_arguments)]
+pub fn sync_ts_types(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    http_client: &dyn HttpClient,
+    agent: &Agent,
+    cwd: &Path,
+    workspace_root: bool,
+    prune: bool,
+    settings: &Settings,
+) -> Result<()> {
+    let package_json_path = find_in_parents(fs, cwd, "package.json").with_context(|| {
+        format!("Couldn't find package.json in the current directory: {cwd:?} or its parents.")
+    })?;
+    let manifest = PackageManifest::load(fs, &package_json_path)?;
+    let project_dir = package_json_path.parent().unwrap();
+
+    let (types_packages, packages): (Vec<_>, Vec<_>) = list_dependencies(&manifest)
+        .into_iter()
+        .partition(|package| package.starts_with("@types/"));
+    install_ts_types(
+        executor,
+        fs,
+        http_client,
+        agent,
+        project_dir,
+        &packages,
+        workspace_root,
+        settings, // explicit sync ignores `enabled` setting but still respects allow and deny lists
+    )?;
+
+    let orphaned_types_packages = find_orphaned_types_packages(&types_packages, &packages);
+    if orphaned_types_packages.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    if prune {
+        println!(
+            "Removing types of packages that are no longer dependencies: {}",
+            orphaned_types_packages.join(", ").dimmed()
+        );
+
+        remove(
+            executor,
+            agent,
+            workspace_root,
+            &orphaned_types_packages,
+            true, // silence output of PM's remove command. The command itself is still printed.
+            settings,
+        )
+    } else {
+        println!(
+            "Found types of packages that are no longer dependencies: {}",
+            orphaned_types_packages.join(", ").dimmed()
+        );
+        println!("Run {} to remove them.", "ny types sync --prune".bold());
+
+        Ok(())
+    }
+}
+
+fn list_dependencies(manifest: &PackageManifest) -> Vec<String> {
+    manifest
+        .dependencies
+        .keys()
+        .chain(manifest.dev_dependencies.keys())
+        .cloned()
+        .collect()
+}
+
+fn find_orphaned_types_packages(types_packages: &[String], packages: &[String]) -> Vec<String> {
+    types_packages
+        .iter()
+        .filter(|types_package| !AMBIENT_TYPES_PACKAGES.contains(&types_package.as_str()))
+        .filter(|types_package| {
+            !packages.contains(&types_package_name_to_package_name(types_package))
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        execute::{expect_execute_once, MockExecutor},
+        fs::{test_utils::expect_file, MockFilesystem},
+        http::{test_utils::expect_package_exist_in_registry, MockHttpClient},
+        utils::vec_of_strings,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_list_dependencies() {
+        let manifest = PackageManifest::from_value(&serde_json::json!({
+            "dependencies": { "a": "^1.0.0" },
+            "devDependencies": { "@types/b": "^1.0.0" },
+            "peerDependencies": { "c": "^1.0.0" }
+        }));
+
+        assert_eq!(
+            list_dependencies(&manifest),
+            vec_of_strings!["a", "@types/b"]
+        );
+    }
+
+    #[test]
+    fn test_find_orphaned_types_packages() {
+        assert_eq!(
+            find_orphaned_types_packages(
+                &["@types/a", "@types/b", "@types/foo__bar", "@types/node"].map(String::from),
+                &["a", "@foo/bar"].map(String::from)
+            ),
+            vec_of_strings!["@types/b"]
+        );
+    }
+
+    #[test]
+    fn test_integration_sync_ts_types() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{
+                "dependencies": { "package-a": "^1.0.0" },
+                "devDependencies": { "@types/package-b": "^1.0.0" }
+            }"#
+            .to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/node_modules/package-a/package.json").to_owned(),
+            r#"{}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "yarn",
+            vec_of_strings!["add", "-D", "@types/package-a"],
+            None,
+            true,
+            true,
+        );
+        expect_execute_once(
+            &mut mock_executor,
+            "yarn",
+            vec_of_strings!["remove", "@types/package-b"],
+            None,
+            true,
+            true,
+        );
+
+        let mut mock_http_client = MockHttpClient::new();
+        expect_package_exist_in_registry(&mut mock_http_client, "@types/package-a", true);
+
+        sync_ts_types(
+            &mock_executor,
+            &mock_fs,
+            &mock_http_client,
+            &Agent::Yarn,
+            Path::new("/project"),
+            false,
+            true,
+            &Settings::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_sync_ts_types_skips_declared_types() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{
+                "dependencies": { "package-a": "^1.0.0" },
+                "devDependencies": { "@types/package-a": "^1.0.0" }
+            }"#
+            .to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/node_modules/package-a/package.json").to_owned(),
+            r#"{}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        // @types/package-a isn't installed yet but it's declared so nothing is added or removed
+        sync_ts_types(
+            &MockExecutor::new(),
+            &mock_fs,
+            &MockHttpClient::new(),
+            &Agent::Yarn,
+            Path::new("/project"),
+            false,
+            true,
+            &Settings::default(),
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+use super::install_ts_types::package_name_without_version;
+
+/// Decides whether (and for which packages) missing @types should be installed automatically.
+///
+/// Configured with `types` setting (see [`crate::config::Settings`]), ex. in package.json:
+/// ```json
+/// "ny": { "types": false }
+/// "ny": { "types": { "enabled": true, "allow": ["react"], "deny": ["lodash"] } }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypesPolicy {
+    pub enabled: bool,
+    /// when set only these packages will get their types installed
+    pub allow: Option<Vec<String>>,
+    /// packages that will never get their types installed
+    pub deny: Vec<String>,
+}
+
+impl Default for TypesPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow: None,
+            deny: vec![],
+        }
+    }
+}
+
+impl TypesPolicy {
+    /// Applies `types` setting which is either a bool or an object with `enabled`, `allow` and `deny` keys
+    pub fn apply(&mut self, types: &serde_json::Value) {
+        if let Some(enabled) = types.as_bool() {
+            self.enabled = enabled;
+            return;
+        }
+        if let Some(enabled) = types.get("enabled").and_then(|enabled| enabled.as_bool()) {
+            self.enabled = enabled;
+        }
+        if let Some(allow) = types.get("allow") {
+            self.allow = Some(string_list(allow));
+        }
+        if let Some(deny) = types.get("deny") {
+            self.deny = string_list(deny);
+        }
+    }
+
+    pub fn allows(&self, package: &str) -> bool {
+        let package = package_name_without_version(package);
+
+        if self.deny.iter().any(|denied| denied == package) {
+            return false;
+        }
+        match &self.allow {
+            Some(allow) => allow.iter().any(|allowed| allowed == package),
+            None => true,
+        }
+    }
+
+    pub fn filter(&self, packages: &[String]) -> Vec<String> {
+        packages
+            .iter()
+            .filter(|package| self.allows(package))
+            .cloned()
+            .collect()
+    }
+}
+
+fn string_list(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|v| v.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::vec_of_strings;
+
+    use super::*;
+
+    #[test]
+    fn test_apply_disabled() {
+        let mut policy = TypesPolicy::default();
+        policy.apply(&serde_json::json!(false));
+
+        assert!(!policy.enabled);
+    }
+
+    #[test]
+    fn test_apply_lists() {
+        let mut policy = TypesPolicy::default();
+        policy.apply(&serde_json::json!({ "allow": ["react", "lodash"], "deny": ["lodash"] }));
+
+        assert!(policy.enabled);
+        assert!(policy.allows("react@18"));
+        assert!(!policy.allows("lodash"));
+        assert!(!policy.allows("express"));
+        assert_eq!(
+            policy.filter(&["react", "lodash", "express"].map(String::from)),
+            vec_of_strings!["react"]
+        );
+    }
+
+    #[test]
+    fn test_apply_keeps_previous_lists() {
+        let mut policy = TypesPolicy::default();
+        policy.apply(&serde_json::json!({ "deny": ["lodash"] }));
+        policy.apply(&serde_json::json!({ "enabled": false }));
+
+        assert!(!policy.enabled);
+        assert_eq!(policy.deny, vec_of_strings!["lodash"]);
+    }
+}
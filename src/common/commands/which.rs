@@ -0,0 +1,267 @@
+use colored::Colorize;
+use eyre::{Report, Result};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    agent::Agent, bootstrap::find_package_manager_field, execute::Executor, fs::Filesystem,
+};
+
+/// What decided about the package manager, from the highest precedence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AgentSource {
+    /// --agent option or NY_AGENT env var
+    Override,
+    Lockfile,
+    /// "agent" setting used when there is no lockfile
+    Setting,
+}
+
+impl AgentSource {
+    fn name(&self) -> &'static str {
+        match self {
+            AgentSource::Override => "override",
+            AgentSource::Lockfile => "lockfile",
+            AgentSource::Setting => "setting",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            AgentSource::Override => "--agent option or NY_AGENT env var",
+            AgentSource::Lockfile => "lockfile",
+            AgentSource::Setting => "\"agent\" setting",
+        }
+    }
+}
+
+/// Explains how the package manager was chosen
+#[derive(Debug, PartialEq)]
+pub struct AgentReport {
+    pub agent: Option<Agent>,
+    pub source: Option<AgentSource>,
+    pub lockfile: Option<PathBuf>,
+    pub binary: Option<PathBuf>,
+    pub binary_version: Option<String>,
+    pub package_manager_field: Option<String>,
+    /// lockfiles that were found while walking parents but didn't decide about the agent
+    pub other_lockfiles: Vec<(Agent, PathBuf)>,
+}
+
+pub fn which(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    cwd: &Path,
+    agent_override: Option<Agent>,
+    agent_setting: Option<Agent>,
+    settings_error: Option<&Report>,
+    json: bool,
+) -> Result<()> {
+    let report = explain_agent(executor, fs, cwd, agent_override, agent_setting);
+    let settings_error = settings_error.map(|error| format!("{error:#}"));
+
+    if json {
+        let mut json = report_to_json(&report);
+        json["settingsError"] = serde_json::json!(settings_error);
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        if let Some(error) = settings_error {
+            println!(
+                "{}",
+                format!("Couldn't load settings, \"agent\" setting is ignored: {error}").yellow()
+            );
+        }
+        print_report(&report);
+    }
+
+    Ok(())
+}
+
+pub fn explain_agent(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    cwd: &Path,
+    agent_override: Option<Agent>,
+    agent_setting: Option<Agent>,
+) -> AgentReport {
+    let mut lockfiles = Agent::find_all_lockfiles(fs, cwd);
+    let recognized = if lockfiles.is_empty() {
+        None
+    } else {
+        Some(lockfiles.remove(0))
+    };
+
+    // same precedence as when running commands
+    let (agent, source) = match (agent_override, &recognized, agent_setting) {
+        (Some(agent), _, _) => (Some(agent), Some(AgentSource::Override)),
+        (None, Some((agent, _)), _) => (Some(*agent), Some(AgentSource::Lockfile)),
+        (None, None, Some(agent)) => (Some(agent), Some(AgentSource::Setting)),
+        (None, None, None) => (None, None),
+    };
+    let binary = agent.and_then(|agent| executor.which(agent.program()));
+    let binary_version = binary.as_ref().and(agent).and_then(|agent| {
+        executor
+            .output(agent.program(), &["--version"])
+            .ok()
+            .and_then(|version| version.lines().next().map(|line| line.trim().to_string()))
+            .filter(|version| !version.is_empty())
+    });
+    let package_manager_field = find_package_manager_field(fs, cwd);
+
+    AgentReport {
+        agent,
+        source,
+        lockfile: recognized.map(|(_, path)| path),
+        binary,
+        binary_version,
+        package_manager_field,
+        other_lockfiles: lockfiles,
+    }
+}
+
+fn print_report(report: &AgentReport) {
+    let not_found = "not found".dimmed();
+
+    match report.agent {
+        Some(agent) => println!("Agent: {}", agent.program().bold()),
+        None => println!("Agent: {not_found}"),
+    }
+    if let Some(source) = report.source {
+        println!("Chosen by: {}", source.description());
+    }
+    match &report.lockfile {
+        Some(lockfile) => {
+            println!("Lockfile: {}", lockfile.display());
+            if let Some(dir) = lockfile.parent() {
+                println!("Directory: {}", dir.display());
+            }
+        }
+        None => println!("Lockfile: {not_found}"),
+    }
+    match (&report.binary, &report.binary_version) {
+        (Some(binary), Some(version)) => println!("Binary: {} ({version})", binary.display()),
+        (Some(binary), None) => println!("Binary: {}", binary.display()),
+        (None, _) => println!("Binary: {}", "not found in PATH".dimmed()),
+    }
+    match &report.package_manager_field {
+        Some(field) => println!("packageManager: {field}"),
+        None => println!("packageManager: {}", "not set".dimmed()),
+    }
+    if !report.other_lockfiles.is_empty() {
+        println!("Other lockfiles:");
+        for (agent, path) in &report.other_lockfiles {
+            println!("  {} ({})", path.display(), agent.program());
+        }
+    }
+}
+
+fn report_to_json(report: &AgentReport) -> serde_json::Value {
+    serde_json::json!({
+        "agent": report.agent.map(|agent| agent.program()),
+        "chosenBy": report.source.map(|source| source.name()),
+        "lockfile": report.lockfile,
+        "directory": report.lockfile.as_ref().and_then(|lockfile| lockfile.parent()),
+        "binary": report.binary,
+        "binaryVersion": report.binary_version,
+        "packageManager": report.package_manager_field,
+        "otherLockfiles": report
+            .other_lockfiles
+            .iter()
+            .map(|(agent, path)| serde_json::json!({ "agent": agent.program(), "path": path }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use crate::execute::MockExecutor;
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+
+    use super::*;
+
+    #[test]
+    fn test_explain_agent() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"packageManager": "pnpm@9.4.0"}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|path| {
+            path == Path::new("/project/pnpm-lock.yaml") || path == Path::new("/yarn.lock")
+        });
+        let mut mock_executor = MockExecutor::new();
+        mock_executor
+            .expect_which()
+            .with(eq("pnpm"))
+            .returning(|_| Some(PathBuf::from("/usr/bin/pnpm")));
+        mock_executor
+            .expect_output()
+            .withf(|program, args| program == "pnpm" && args == ["--version"])
+            .returning(|_, _| Ok("9.4.0\n".to_string()));
+
+        let report = explain_agent(
+            &mock_executor,
+            &mock_fs,
+            Path::new("/project"),
+            None,
+            Some(Agent::Npm),
+        );
+
+        assert_eq!(
+            report,
+            AgentReport {
+                agent: Some(Agent::Pnpm),
+                source: Some(AgentSource::Lockfile),
+                lockfile: Some(PathBuf::from("/project/pnpm-lock.yaml")),
+                binary: Some(PathBuf::from("/usr/bin/pnpm")),
+                binary_version: Some("9.4.0".to_string()),
+                package_manager_field: Some("pnpm@9.4.0".to_string()),
+                other_lockfiles: vec![(Agent::Yarn, PathBuf::from("/yarn.lock"))],
+            }
+        );
+    }
+
+    #[test]
+    fn test_explain_agent_override_without_binary() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|_| false);
+        let mut mock_executor = MockExecutor::new();
+        mock_executor.expect_which().returning(|_| None);
+
+        let report = explain_agent(
+            &mock_executor,
+            &mock_fs,
+            Path::new("/project"),
+            Some(Agent::Bun),
+            Some(Agent::Npm),
+        );
+
+        assert_eq!(report.agent, Some(Agent::Bun));
+        assert_eq!(report.source, Some(AgentSource::Override));
+        assert_eq!(report.lockfile, None);
+        assert_eq!(report.binary, None);
+        assert_eq!(report.binary_version, None);
+    }
+
+    #[test]
+    fn test_explain_agent_setting_without_lockfile() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|_| false);
+        let mut mock_executor = MockExecutor::new();
+        mock_executor.expect_which().returning(|_| None);
+
+        let report = explain_agent(
+            &mock_executor,
+            &mock_fs,
+            Path::new("/project"),
+            None,
+            Some(Agent::Pnpm),
+        );
+
+        assert_eq!(report.agent, Some(Agent::Pnpm));
+        assert_eq!(report.source, Some(AgentSource::Setting));
+        assert_eq!(report.lockfile, None);
+    }
+}
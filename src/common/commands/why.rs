@@ -0,0 +1,551 @@
+use colored::Colorize;
+use eyre::Result;
+use std::path::Path;
+
+use crate::agent::Agent;
+use crate::config::Settings;
+use crate::execute::Executor;
+use crate::fs::Filesystem;
+use crate::lockfile::{LockedPackage, Lockfile};
+
+use super::outdated::is_yarn_classic;
+
+// guards against dependency cycles
+const MAX_PATH_LENGTH: usize = 32;
+
+/// Paths from the project (or a dependent) down to the package
+type DependencyPaths = Vec<Vec<PackageRef>>;
+
+/// Package in a dependency path
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageRef {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// Explains why the package is installed. Dependency paths are read from the lockfile when it records
+/// direct dependencies of the project, otherwise output of npm, pnpm and yarn v2+ is turned into them.
+/// Paths are printed as a tree, other package managers print out their own explanation
+pub fn why(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    agent: &Agent,
+    cwd: &Path,
+    package: &str,
+    settings: &Settings,
+) -> Result<()> {
+    // yarn v1 and binary bun lockfiles can't tell where the paths start
+    let locked_paths = Agent::recognize_lockfile(fs, cwd)
+        .and_then(|(_, path)| Lockfile::load(fs, &path).ok())
+        .filter(|lockfile| !lockfile.importers.is_empty())
+        .map(|lockfile| lockfile_paths(&lockfile, package));
+
+    let paths = match locked_paths {
+        Some(paths) => paths,
+        None => match agent_paths(executor, agent, package) {
+            Ok(paths) => paths,
+            Err(args) => {
+                return executor.execute(agent.program(), &args, None, settings.verbose, false)
+            }
+        },
+    };
+
+    if paths.is_empty() {
+        println!("{package} is not installed");
+    } else {
+        print_tree(&build_tree(&paths), "");
+    }
+
+    Ok(())
+}
+
+/// Asks the package manager, arguments for its own explanation are returned when its output can't be read
+fn agent_paths<'a>(
+    executor: &dyn Executor,
+    agent: &Agent,
+    package: &'a str,
+) -> Result<DependencyPaths, Vec<&'a str>> {
+    type Parser = fn(&str, &str) -> Option<DependencyPaths>;
+    let (args, parse): (Vec<&str>, Option<Parser>) = match agent {
+        Agent::Npm => (vec!["explain", package, "--json"], Some(parse_npm_output)),
+        Agent::Pnpm => (vec!["why", package, "--json"], Some(parse_pnpm_output)),
+        Agent::Yarn if !is_yarn_classic(executor) => (
+            vec!["why", package, "--json"],
+            Some(parse_yarn_berry_output),
+        ),
+        Agent::Yarn | Agent::Bun => (vec!["why", package], None),
+    };
+
+    let paths = parse.and_then(|parse| {
+        let output = executor.output(agent.program(), &args).ok()?;
+        parse(&output, package)
+    });
+
+    paths.ok_or_else(|| args.into_iter().filter(|arg| *arg != "--json").collect())
+}
+
+/// Walks up from every locked version of the package through its dependents to the projects (importers)
+fn lockfile_paths(lockfile: &Lockfile, package: &str) -> DependencyPaths {
+    let mut paths = vec![];
+    for locked in lockfile.find(package) {
+        collect_lockfile_paths(lockfile, locked, vec![], &mut paths);
+    }
+
+    paths
+}
+
+fn collect_lockfile_paths(
+    lockfile: &Lockfile,
+    locked: &LockedPackage,
+    mut path: Vec<PackageRef>,
+    paths: &mut DependencyPaths,
+) {
+    path.insert(
+        0,
+        PackageRef {
+            name: locked.name.clone(),
+            version: Some(locked.version.clone()),
+        },
+    );
+
+    for (importer, dependencies) in &lockfile.importers {
+        if dependencies.get(&locked.name) == Some(&locked.version) {
+            let mut path = path.clone();
+            path.insert(
+                0,
+                PackageRef {
+                    // workspaces are keyed by their path relative to the lockfile
+                    name: if importer.is_empty() { "." } else { importer }.to_string(),
+                    version: None,
+                },
+            );
+            paths.push(path);
+        }
+    }
+    if path.len() >= MAX_PATH_LENGTH {
+        return;
+    }
+
+    for dependent in lockfile.dependents(&locked.name, &locked.version) {
+        let in_path = path.iter().any(|package| {
+            package.name == dependent.name && package.version.as_ref() == Some(&dependent.version)
+        });
+        if !in_path {
+            collect_lockfile_paths(lockfile, dependent, path.clone(), paths);
+        }
+    }
+}
+
+/// `npm explain --json` lists installed copies of the package, each with dependents leading up to the project
+fn parse_npm_output(output: &str, _package: &str) -> Option<DependencyPaths> {
+    let nodes: serde_json::Value = serde_json::from_str(output).ok()?;
+
+    let mut paths = vec![];
+    for node in nodes.as_array()? {
+        collect_npm_paths(node, vec![], &mut paths);
+    }
+
+    Some(paths)
+}
+
+fn collect_npm_paths(
+    node: &serde_json::Value,
+    mut path: Vec<PackageRef>,
+    paths: &mut DependencyPaths,
+) {
+    path.insert(0, package_ref(node));
+
+    let dependents = node
+        .get("dependents")
+        .and_then(|dependents| dependents.as_array())
+        .filter(|dependents| !dependents.is_empty());
+    match dependents {
+        // chain ends with the project itself. npm truncates long chains, path then starts from the last
+        // dependent it knows about
+        Some(dependents) if path.len() < MAX_PATH_LENGTH => {
+            for dependent in dependents {
+                match dependent.get("from") {
+                    Some(from) => collect_npm_paths(from, path.clone(), paths),
+                    None => paths.push(path.clone()),
+                }
+            }
+        }
+        _ => paths.push(path),
+    }
+}
+
+/// `pnpm why --json` prints dependency trees of projects, pruned to branches leading to the package
+fn parse_pnpm_output(output: &str, package: &str) -> Option<DependencyPaths> {
+    let projects: serde_json::Value = serde_json::from_str(output).ok()?;
+
+    let mut paths = vec![];
+    for project in projects.as_array()? {
+        collect_pnpm_paths(project, package_ref(project), package, vec![], &mut paths);
+    }
+
+    Some(paths)
+}
+
+fn collect_pnpm_paths(
+    node: &serde_json::Value,
+    package_ref: PackageRef,
+    package: &str,
+    mut path: Vec<PackageRef>,
+    paths: &mut DependencyPaths,
+) {
+    let is_target = !path.is_empty() && package_ref.name == package;
+    path.push(package_ref);
+    if is_target {
+        paths.push(path);
+        return;
+    }
+
+    for group in ["dependencies", "devDependencies", "optionalDependencies"] {
+        let Some(dependencies) = node.get(group).and_then(|deps| deps.as_object()) else {
+            continue;
+        };
+        for (name, dependency) in dependencies {
+            let dependency_ref = PackageRef {
+                name: name.clone(),
+                version: dependency
+                    .get("version")
+                    .and_then(|v| v.as_str())
+                    .map(String::from),
+            };
+            collect_pnpm_paths(dependency, dependency_ref, package, path.clone(), paths);
+        }
+    }
+}
+
+/// `yarn why --json` prints a JSON line per dependent, with locators like `react-dom@npm:18.2.0`
+fn parse_yarn_berry_output(output: &str, _package: &str) -> Option<DependencyPaths> {
+    let mut paths = vec![];
+    for line in output.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: serde_json::Value = serde_json::from_str(line).ok()?;
+        let dependent = parse_locator(entry.get("value")?.as_str()?);
+        for (child, _) in entry.get("children")?.as_object()? {
+            paths.push(vec![dependent.clone(), parse_locator(child)]);
+        }
+    }
+
+    Some(paths)
+}
+
+fn parse_locator(locator: &str) -> PackageRef {
+    // scoped packages start with @ so the separator is searched after the first character
+    let Some(at) = locator
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == '@')
+        .map(|(at, _)| at)
+    else {
+        return PackageRef {
+            name: locator.to_string(),
+            version: None,
+        };
+    };
+    let reference = &locator[at + 1..];
+
+    PackageRef {
+        name: locator[..at].to_string(),
+        version: reference
+            .strip_prefix("npm:")
+            .map(|version| version.to_string()),
+    }
+}
+
+fn package_ref(node: &serde_json::Value) -> PackageRef {
+    PackageRef {
+        name: node
+            .get("name")
+            .and_then(|name| name.as_str())
+            .unwrap_or(".")
+            .to_string(),
+        version: node
+            .get("version")
+            .and_then(|version| version.as_str())
+            .map(String::from),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct TreeNode {
+    package: PackageRef,
+    children: Vec<TreeNode>,
+}
+
+/// Merges paths sharing the same beginning
+fn build_tree(paths: &[Vec<PackageRef>]) -> Vec<TreeNode> {
+    let mut nodes = vec![];
+    for path in paths {
+        merge_path(&mut nodes, path);
+    }
+
+    nodes
+}
+
+fn merge_path(nodes: &mut Vec<TreeNode>, path: &[PackageRef]) {
+    let Some((first, rest)) = path.split_first() else {
+        return;
+    };
+    match nodes.iter_mut().find(|node| &node.package == first) {
+        Some(node) => merge_path(&mut node.children, rest),
+        None => {
+            let mut node = TreeNode {
+                package: first.clone(),
+                children: vec![],
+            };
+            merge_path(&mut node.children, rest);
+            nodes.push(node);
+        }
+    }
+}
+
+fn print_tree(nodes: &[TreeNode], indent: &str) {
+    for (i, node) in nodes.iter().enumerate() {
+        let label = match &node.package.version {
+            Some(version) => format!("{}{}", node.package.name, format!("@{version}").dimmed()),
+            None => node.package.name.bold().to_string(),
+        };
+        if indent.is_empty() {
+            println!("{label}");
+            print_tree(&node.children, " ");
+            continue;
+        }
+
+        let last = i == nodes.len() - 1;
+        println!("{indent}{} {label}", if last { "└─" } else { "├─" });
+        print_tree(
+            &node.children,
+            &format!("{indent}{}", if last { "   " } else { "│  " }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::execute::{expect_execute_once, MockExecutor};
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+
+    use super::*;
+
+    fn package(name: &str, version: Option<&str>) -> PackageRef {
+        PackageRef {
+            name: name.to_string(),
+            version: version.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_parse_npm_output() {
+        let output = r#"[{
+            "name": "scheduler", "version": "0.23.0", "location": "node_modules/scheduler",
+            "dependents": [{
+                "type": "prod", "name": "scheduler", "spec": "^0.23.0",
+                "from": {
+                    "name": "react-dom", "version": "18.2.0", "location": "node_modules/react-dom",
+                    "dependents": [{ "type": "prod", "name": "react-dom", "spec": "^18", "from": { "location": "/project" } }]
+                }
+            }]
+        }]"#;
+
+        assert_eq!(
+            parse_npm_output(output, "scheduler"),
+            Some(vec![vec![
+                package(".", None),
+                package("react-dom", Some("18.2.0")),
+                package("scheduler", Some("0.23.0")),
+            ]])
+        );
+        assert_eq!(parse_npm_output("npm ERR!", "scheduler"), None);
+    }
+
+    #[test]
+    fn test_parse_pnpm_output() {
+        let output = r#"[{
+            "name": "app", "version": "1.0.0", "path": "/project",
+            "dependencies": {
+                "react-dom": { "from": "react-dom", "version": "18.2.0", "dependencies": {
+                    "scheduler": { "from": "scheduler", "version": "0.23.0" }
+                } }
+            },
+            "devDependencies": { "scheduler": { "from": "scheduler", "version": "0.23.0" } }
+        }]"#;
+
+        assert_eq!(
+            parse_pnpm_output(output, "scheduler"),
+            Some(vec![
+                vec![
+                    package("app", Some("1.0.0")),
+                    package("react-dom", Some("18.2.0")),
+                    package("scheduler", Some("0.23.0")),
+                ],
+                vec![
+                    package("app", Some("1.0.0")),
+                    package("scheduler", Some("0.23.0")),
+                ],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_yarn_berry_output() {
+        let output = r#"{"value":"app@workspace:.","children":{"@scope/a@npm:1.0.0":{"descriptor":"@scope/a@npm:^1","locator":"@scope/a@npm:1.0.0"}}}
+{"value":"@scope/b@npm:2.0.0","children":{"@scope/a@npm:1.0.0":{"descriptor":"@scope/a@npm:^1","locator":"@scope/a@npm:1.0.0"}}}"#;
+
+        assert_eq!(
+            parse_yarn_berry_output(output, "@scope/a"),
+            Some(vec![
+                vec![package("app", None), package("@scope/a", Some("1.0.0"))],
+                vec![
+                    package("@scope/b", Some("2.0.0")),
+                    package("@scope/a", Some("1.0.0"))
+                ],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let root = package("app", None);
+        let a = package("a", Some("1.0.0"));
+        let b = package("b", Some("1.0.0"));
+        let c = package("c", Some("1.0.0"));
+
+        assert_eq!(
+            build_tree(&[
+                vec![root.clone(), a.clone(), c.clone()],
+                vec![root.clone(), b.clone(), c.clone()],
+                vec![root.clone(), c.clone()],
+            ]),
+            vec![TreeNode {
+                package: root,
+                children: vec![
+                    TreeNode {
+                        package: a,
+                        children: vec![TreeNode {
+                            package: c.clone(),
+                            children: vec![]
+                        }]
+                    },
+                    TreeNode {
+                        package: b,
+                        children: vec![TreeNode {
+                            package: c.clone(),
+                            children: vec![]
+                        }]
+                    },
+                    TreeNode {
+                        package: c,
+                        children: vec![]
+                    },
+                ]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_why_falls_back_to_agent_output() {
+        let mut mock_executor = MockExecutor::new();
+        expect_execute_once(
+            &mut mock_executor,
+            "bun",
+            vec!["why".to_string(), "zod".to_string()],
+            None,
+            true,
+            false,
+        );
+
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|_| false);
+
+        why(
+            &mock_executor,
+            &mock_fs,
+            &Agent::Bun,
+            Path::new("/project"),
+            "zod",
+            &Settings::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_lockfile_paths() {
+        let lockfile = crate::lockfile::pnpm::parse(
+            r#"
+lockfileVersion: '9.0'
+importers:
+  .:
+    dependencies:
+      react-dom:
+        specifier: ^18
+        version: 18.2.0(react@18.2.0)
+  packages/lib:
+    dependencies:
+      scheduler:
+        specifier: ^0.22
+        version: 0.22.0
+packages:
+  react-dom@18.2.0: {}
+  scheduler@0.22.0: {}
+  scheduler@0.23.0: {}
+snapshots:
+  react-dom@18.2.0(react@18.2.0):
+    dependencies:
+      scheduler: 0.23.0
+  scheduler@0.22.0: {}
+  scheduler@0.23.0: {}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile_paths(&lockfile, "scheduler"),
+            vec![
+                vec![
+                    package("packages/lib", None),
+                    package("scheduler", Some("0.22.0")),
+                ],
+                vec![
+                    package(".", None),
+                    package("react-dom", Some("18.2.0")),
+                    package("scheduler", Some("0.23.0")),
+                ],
+            ]
+        );
+        assert_eq!(lockfile_paths(&lockfile, "zod"), DependencyPaths::new());
+    }
+
+    #[test]
+    fn test_why_reads_lockfile() {
+        // nothing is spawned when the lockfile answers
+        let mock_executor = MockExecutor::new();
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/package-lock.json"),
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "dependencies": { "zod": "^3" } },
+                    "node_modules/zod": { "version": "3.23.8" }
+                }
+            }"#
+            .to_string(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        why(
+            &mock_executor,
+            &mock_fs,
+            &Agent::Npm,
+            Path::new("/project"),
+            "zod",
+            &Settings::default(),
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+use clap::ValueEnum;
+use eyre::{eyre, Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::agent::Agent;
+use crate::commands::types_policy::TypesPolicy;
+use crate::fs::{find_in_parents, Filesystem};
+use crate::manifest::PackageManifest;
+use crate::utils::parse_bool;
+
+const PROJECT_CONFIG_FILES: &[&str] = &["ny.toml", ".nyrc"];
+
+/// Settings changing default behaviour of ny. Merged from (later ones win):
+/// - user config: `~/.config/ny/config.toml`
+/// - project config: closest `ny.toml` or `.nyrc` (both in TOML format)
+/// - `ny` key in the closest package.json
+/// - `NY_VERBOSE`, `NY_FROZEN`, `NY_TYPES`, `NY_SHIMS`, `NY_BOOTSTRAP` and `NY_VERSION_CHECK` env vars
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// print out commands executed by ny
+    pub verbose: bool,
+    /// install dependencies without updating the lockfile (ex. `npm ci`)
+    pub frozen: bool,
+    /// agent used when there is no lockfile
+    pub agent: Option<Agent>,
+    pub types: TypesPolicy,
+    /// what to do when installed agent's version differs from the one in `packageManager` field
+    pub version_check: VersionCheck,
+    /// put shims forwarding other package managers' calls in scripts to ny on PATH of `ny run`
+    pub shims: bool,
+    /// run package managers missing in PATH through corepack or npx
+    pub bootstrap: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum VersionCheck {
+    Off,
+    Warn,
+    Error,
+    /// run declared version through corepack (or npx)
+    Corepack,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            verbose: true,
+            frozen: false,
+            agent: None,
+            types: TypesPolicy::default(),
+            version_check: VersionCheck::Warn,
+            shims: false,
+            bootstrap: false,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(
+        fs: &dyn Filesystem,
+        cwd: &Path,
+        user_config_dir: Option<&Path>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut settings = Self::default();
+
+        let user_config_path = user_config_dir.map(|dir| dir.join("ny/config.toml"));
+        if let Some(path) = user_config_path.filter(|path| fs.exists(path)) {
+            settings.apply_toml_file(fs, &path)?;
+        }
+        if let Some(path) = find_project_config(fs, cwd) {
+            settings.apply_toml_file(fs, &path)?;
+        }
+        if let Some(path) = find_in_parents(fs, cwd, "package.json") {
+            if let Some(config) = PackageManifest::load(fs, &path)
+                .ok()
+                .and_then(|manifest| manifest.ny)
+            {
+                settings
+                    .apply(&config)
+                    .with_context(|| format!("Invalid \"ny\" key in {path:?}"))?;
+            }
+        }
+
+        if let Some(verbose) = env("NY_VERBOSE").as_deref().and_then(parse_bool) {
+            settings.verbose = verbose;
+        }
+        if let Some(frozen) = env("NY_FROZEN").as_deref().and_then(parse_bool) {
+            settings.frozen = frozen;
+        }
+        if let Some(types) = env("NY_TYPES").as_deref().and_then(parse_bool) {
+            settings.types.enabled = types;
+        }
+        if let Some(shims) = env("NY_SHIMS").as_deref().and_then(parse_bool) {
+            settings.shims = shims;
+        }
+        if let Some(bootstrap) = env("NY_BOOTSTRAP").as_deref().and_then(parse_bool) {
+            settings.bootstrap = bootstrap;
+        }
+        if let Some(version_check) = env("NY_VERSION_CHECK") {
+            settings.version_check = parse_version_check(&version_check)?;
+        }
+
+        Ok(settings)
+    }
+
+    /// Applies config object. Missing keys leave current values untouched
+    pub fn apply(&mut self, config: &serde_json::Value) -> Result<()> {
+        if let Some(verbose) = config.get("verbose").and_then(|verbose| verbose.as_bool()) {
+            self.verbose = verbose;
+        }
+        if let Some(frozen) = config.get("frozen").and_then(|frozen| frozen.as_bool()) {
+            self.frozen = frozen;
+        }
+        if let Some(agent) = config.get("agent").and_then(|agent| agent.as_str()) {
+            self.agent =
+                Some(Agent::from_str(agent, true).map_err(|_| eyre!("Unknown agent: {agent}"))?);
+        }
+        if let Some(shims) = config.get("shims").and_then(|shims| shims.as_bool()) {
+            self.shims = shims;
+        }
+        if let Some(bootstrap) = config
+            .get("bootstrap")
+            .and_then(|bootstrap| bootstrap.as_bool())
+        {
+            self.bootstrap = bootstrap;
+        }
+        if let Some(types) = config.get("types") {
+            self.types.apply(types);
+        }
+        if let Some(version_check) = config
+            .get("version_check")
+            .and_then(|version_check| version_check.as_str())
+        {
+            self.version_check = parse_version_check(version_check)?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_toml_file(&mut self, fs: &dyn Filesystem, path: &Path) -> Result<()> {
+        let config = fs
+            .read_to_string(path)
+            .ok()
+            .and_then(|contents| toml::from_str::<toml::Value>(&contents).ok())
+            .and_then(|config| serde_json::to_value(config).ok())
+            .ok_or_else(|| eyre!("Couldn't parse config file: {path:?}"))?;
+
+        self.apply(&config)
+            .with_context(|| format!("Invalid config file: {path:?}"))
+    }
+}
+
+fn parse_version_check(value: &str) -> Result<VersionCheck> {
+    VersionCheck::from_str(value, true).map_err(|_| {
+        eyre!("Unknown version_check: {value}, expected one of: off, warn, error, corepack")
+    })
+}
+
+/// Directory containing user configs, usually `~/.config`
+pub fn user_config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+fn find_project_config(fs: &dyn Filesystem, cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors().find_map(|dir| {
+        PROJECT_CONFIG_FILES
+            .iter()
+            .map(|config_file| dir.join(config_file))
+            .find(|path| fs.exists(path))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+
+    use super::*;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_load_default() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|_| false);
+
+        let settings = Settings::load(&mock_fs, Path::new("/project"), None, &no_env).unwrap();
+
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn test_load_merges_configs() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/home/user/.config/ny/config.toml").to_owned(),
+            "verbose = false\nagent = \"pnpm\"\n[types]\ndeny = [\"lodash\"]\n".to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/.nyrc").to_owned(),
+            "frozen = true\nagent = \"bun\"\nversion_check = \"corepack\"\n".to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"ny": {"types": {"enabled": false}}}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        let settings = Settings::load(
+            &mock_fs,
+            Path::new("/project/src"),
+            Some(Path::new("/home/user/.config")),
+            &no_env,
+        )
+        .unwrap();
+
+        assert!(!settings.verbose);
+        assert!(settings.frozen);
+        assert_eq!(settings.agent, Some(Agent::Bun));
+        assert_eq!(settings.version_check, VersionCheck::Corepack);
+        assert!(!settings.types.enabled);
+        assert_eq!(settings.types.deny, vec!["lodash".to_string()]);
+    }
+
+    #[test]
+    fn test_load_env_overrides() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/package.json").to_owned(),
+            r#"{"ny": {"types": false, "verbose": false}}"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        let settings = Settings::load(&mock_fs, Path::new("/project"), None, &|name| match name {
+            "NY_TYPES" => Some("1".to_string()),
+            "NY_FROZEN" => Some("yes".to_string()),
+            "NY_VERSION_CHECK" => Some("error".to_string()),
+            "NY_SHIMS" => Some("true".to_string()),
+            "NY_BOOTSTRAP" => Some("1".to_string()),
+            _ => None,
+        })
+        .unwrap();
+
+        assert!(settings.types.enabled);
+        assert!(settings.frozen);
+        assert!(!settings.verbose);
+        assert_eq!(settings.version_check, VersionCheck::Error);
+        assert!(settings.shims);
+        assert!(settings.bootstrap);
+    }
+
+    #[test]
+    fn test_load_invalid_config() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/ny.toml").to_owned(),
+            "agent = \"deno\"".to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert!(Settings::load(&mock_fs, Path::new("/project"), None, &no_env).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use colored::Colorize;
 use eyre::{Context, Result};
+use std::path::{Path, PathBuf};
 
 use mockall::automock;
 
@@ -14,9 +15,19 @@ pub trait Executor {
         verbose: bool,        // print out the command being executed
         silence_stdout: bool, // silence "regular" output, still print out errors
     ) -> Result<()>;
+
+    /// Runs the command and returns its stdout, even if it exited with non-zero code. Doesn't print anything
+    #[allow(clippy::needless_lifetimes)]
+    fn output<'a>(&self, program: &str, args: &[&'a str]) -> Result<String>;
+
+    /// Path to the program's executable found in PATH
+    fn which(&self, program: &str) -> Option<PathBuf>;
 }
 
-pub struct RealExecutor {}
+pub struct RealExecutor {
+    /// working directory of spawned processes, inherited from ny when not set
+    pub cwd: Option<PathBuf>,
+}
 impl Executor for RealExecutor {
     fn execute(
         &self,
@@ -27,7 +38,7 @@ impl Executor for RealExecutor {
         silence_stdout: bool,
     ) -> Result<()> {
         if verbose {
-            println!("{}", format!("$ {} {}", program, args.join(" ")).dimmed());
+            print_command(program, args);
         }
 
         let mut cmd_builder_ref = std::process::Command::new(program);
@@ -37,6 +48,9 @@ impl Executor for RealExecutor {
             let current_path = std::env::var("PATH").unwrap_or_else(|_| "".to_string());
             cmd_builder.env("PATH", extra_env + ":" + &current_path);
         }
+        if let Some(cwd) = &self.cwd {
+            cmd_builder.current_dir(cwd);
+        }
         if silence_stdout {
             cmd_builder.stdout(std::process::Stdio::null());
         }
@@ -53,6 +67,77 @@ impl Executor for RealExecutor {
 
         Ok(())
     }
+
+    fn output(&self, program: &str, args: &[&str]) -> Result<String> {
+        capture_output(self.cwd.as_deref(), program, args)
+    }
+
+    fn which(&self, program: &str) -> Option<PathBuf> {
+        find_in_path(program)
+    }
+}
+
+/// Only prints out commands that would be executed. Queries (`output` and `which`) are still performed
+pub struct DryRunExecutor {
+    pub cwd: Option<PathBuf>,
+}
+impl Executor for DryRunExecutor {
+    fn execute(
+        &self,
+        program: &str,
+        args: &[&str],
+        _extra_path: Option<String>,
+        verbose: bool,
+        _silence_stdout: bool,
+    ) -> Result<()> {
+        // when command isn't verbose the caller prints out more readable version of it by itself
+        if verbose {
+            print_command(program, args);
+        }
+
+        Ok(())
+    }
+
+    fn output(&self, program: &str, args: &[&str]) -> Result<String> {
+        capture_output(self.cwd.as_deref(), program, args)
+    }
+
+    fn which(&self, program: &str) -> Option<PathBuf> {
+        find_in_path(program)
+    }
+}
+
+fn capture_output(cwd: Option<&Path>, program: &str, args: &[&str]) -> Result<String> {
+    let mut cmd_builder = std::process::Command::new(program);
+    cmd_builder.args(args).stderr(std::process::Stdio::null());
+    if let Some(cwd) = cwd {
+        cmd_builder.current_dir(cwd);
+    }
+
+    let output = cmd_builder
+        .output()
+        .with_context(|| format!("Couldn't run command: {program}"))?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+fn print_command(program: &str, args: &[&str]) {
+    println!("{}", format!("$ {} {}", program, args.join(" ")).dimmed());
 }
 
 #[cfg(test)]
@@ -87,8 +172,38 @@ mod tests {
 
     #[test]
     fn test_execute() {
-        let executor = RealExecutor {};
+        let executor = RealExecutor { cwd: None };
         let result = executor.execute("sh", &["-c", "true"], None, false, false);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_execute_in_cwd() {
+        let executor = RealExecutor {
+            cwd: Some(PathBuf::from("/")),
+        };
+        let result = executor.execute("sh", &["-c", "test \"$PWD\" = /"], None, false, false);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_output() {
+        let executor = RealExecutor { cwd: None };
+        let result = executor.output("sh", &["-c", "echo out; echo err >&2; exit 1"]);
+        assert_eq!(result.unwrap(), "out\n");
+    }
+
+    #[test]
+    fn test_which() {
+        let executor = RealExecutor { cwd: None };
+        assert!(executor.which("sh").is_some());
+        assert!(executor.which("surely-not-existing-program").is_none());
+    }
+
+    #[test]
+    fn test_dry_run_execute() {
+        let executor = DryRunExecutor { cwd: None };
+        let result = executor.execute("false", &[], None, true, false);
+        assert!(result.is_ok());
+    }
 }
@@ -1,13 +1,26 @@
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use eyre::Result;
 
 use mockall::automock;
 
+use crate::agent::Agent;
+
 #[automock]
 pub trait Filesystem {
     fn exists(&self, path: &Path) -> bool;
     fn read_to_string(&self, path: &Path) -> Result<String>;
+    fn modified(&self, path: &Path) -> Result<SystemTime>;
+    /// Names of all entries in the directory
+    fn read_dir(&self, path: &Path) -> Result<Vec<String>>;
+    /// Creates or truncates the file
+    fn write(&self, path: &Path, contents: &str) -> Result<()>;
+    fn remove_file(&self, path: &Path) -> Result<()>;
+    /// Removes the directory with all its contents
+    fn remove_dir_all(&self, path: &Path) -> Result<()>;
+    /// Creates or truncates the file along with missing parent directories and makes it executable
+    fn write_executable(&self, path: &Path, contents: &str) -> Result<()>;
 }
 
 pub struct RealFs {}
@@ -19,6 +32,43 @@ impl Filesystem for RealFs {
     fn read_to_string(&self, path: &Path) -> Result<String> {
         Ok(std::fs::read_to_string(path)?)
     }
+
+    fn modified(&self, path: &Path) -> Result<SystemTime> {
+        Ok(std::fs::metadata(path)?.modified()?)
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            names.push(entry?.file_name().to_string_lossy().to_string());
+        }
+
+        Ok(names)
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> Result<()> {
+        Ok(std::fs::write(path, contents)?)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        Ok(std::fs::remove_file(path)?)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<()> {
+        Ok(std::fs::remove_dir_all(path)?)
+    }
+
+    fn write_executable(&self, path: &Path, contents: &str) -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+
+        Ok(())
+    }
 }
 
 pub fn find_in_parents(fs: &dyn Filesystem, dir: &Path, filename: &str) -> Option<PathBuf> {
@@ -30,6 +80,23 @@ pub fn find_in_parents(fs: &dyn Filesystem, dir: &Path, filename: &str) -> Optio
     }
 }
 
+/// Same as `find_in_parents` but stops at the project root (directory with a lockfile or .git) so files of
+/// unrelated projects placed higher in the directory tree are ignored
+pub fn find_in_project(fs: &dyn Filesystem, dir: &Path, filename: &str) -> Option<PathBuf> {
+    if fs.exists(&dir.join(filename)) {
+        Some(dir.join(filename))
+    } else if is_project_root(fs, dir) {
+        None
+    } else {
+        dir.parent()
+            .and_then(|parent| find_in_project(fs, parent, filename))
+    }
+}
+
+pub(crate) fn is_project_root(fs: &dyn Filesystem, dir: &Path) -> bool {
+    Agent::recognize_in_dir(fs, dir).is_some() || fs.exists(&dir.join(".git"))
+}
+
 #[cfg(test)]
 pub mod test_utils {
     use super::*;
@@ -48,6 +115,14 @@ pub mod test_utils {
             .returning(move |_| Ok(contents.clone()));
     }
 
+    pub fn expect_write(mock_fs: &mut MockFilesystem, path: PathBuf, contents: String) {
+        mock_fs
+            .expect_write()
+            .withf(move |p, c| p == path && c == contents)
+            .times(1)
+            .returning(|_, _| Ok(()));
+    }
+
     #[test]
     fn test_find_in_parents_in_root() {
         let mut mock_fs = MockFilesystem::new();
@@ -5,6 +5,8 @@ use mockall::automock;
 #[automock]
 pub trait HttpClient {
     fn request_if_success(&self, url: &str) -> Result<bool>;
+    /// Returns parsed JSON body or None if server didn't respond with success status
+    fn request_json(&self, url: &str) -> Result<Option<serde_json::Value>>;
 }
 
 pub struct RealHttpClient {}
@@ -13,6 +15,22 @@ impl HttpClient for RealHttpClient {
         let resp = reqwest::blocking::get(url)?;
         Ok(resp.status().is_success())
     }
+
+    fn request_json(&self, url: &str) -> Result<Option<serde_json::Value>> {
+        let resp = reqwest::blocking::Client::new()
+            .get(url)
+            // ask registry for abbreviated metadata, it's way smaller than the full document
+            .header(
+                "Accept",
+                "application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8, */*",
+            )
+            .send()?;
+        if !resp.status().is_success() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&resp.text()?)?))
+    }
 }
 
 #[cfg(test)]
@@ -24,11 +42,30 @@ pub mod test_utils {
         mock_http_client: &mut MockHttpClient,
         package: &str,
         success: bool,
+    ) {
+        expect_package_versions_in_registry(
+            mock_http_client,
+            package,
+            if success { Some(&[]) } else { None },
+        );
+    }
+
+    pub fn expect_package_versions_in_registry(
+        mock_http_client: &mut MockHttpClient,
+        package: &str,
+        versions: Option<&[&str]>,
     ) {
         let url = format!("https://registry.npmjs.org/{}", package);
+        let metadata = versions.map(|versions| {
+            let versions = versions
+                .iter()
+                .map(|version| (version.to_string(), serde_json::json!({})))
+                .collect::<serde_json::Map<_, _>>();
+            serde_json::json!({ "name": package, "versions": versions })
+        });
         mock_http_client
-            .expect_request_if_success()
+            .expect_request_json()
             .with(eq(url))
-            .returning(move |_| Ok(success));
+            .returning(move |_| Ok(metadata.clone()));
     }
 }
@@ -0,0 +1,557 @@
+use std::ops::Range;
+
+use crate::agent::Agent;
+use crate::cli::is_command;
+use crate::semver::Version;
+
+// commands that take precedence over scripts with the same name in yarn and pnpm
+const BUILTIN_COMMANDS: &[&str] = &[
+    "add",
+    "bin",
+    "cache",
+    "config",
+    "dlx",
+    "exec",
+    "info",
+    "init",
+    "install",
+    "link",
+    "pack",
+    "publish",
+    "remove",
+    "unlink",
+    "upgrade",
+    "version",
+    "why",
+    "workspace",
+    "workspaces",
+];
+
+/// Package manager call inside a package.json script, ex. `yarn build --watch`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    /// byte range of the whole command in the script
+    pub range: Range<usize>,
+    pub agent: Agent,
+    pub kind: InvocationKind,
+    /// arguments passed through to the task or binary, without npm's `--` separator
+    pub args: Vec<String>,
+    /// arguments npm takes for itself, ex. `--watch` in `npm run build --watch`. They have no counterpart in
+    /// other package managers so such calls are never rewritten
+    pub agent_args: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvocationKind {
+    /// running a script from package.json
+    Run(String),
+    Install {
+        frozen: bool,
+    },
+    /// running a binary of an installed package, ex. `npx tsc` or `pnpm exec tsc`
+    Exec(String),
+    /// running a binary of a package downloaded on the fly, ex. `pnpm dlx` or `npx --package`
+    Dlx {
+        package: Option<String>,
+        binary: String,
+    },
+}
+
+impl Invocation {
+    /// Same call made with another package manager. `agent_version` picks flags that differ between its
+    /// major versions, when it's known
+    pub fn command_for(&self, agent: &Agent, agent_version: Option<&Version>) -> String {
+        let program = agent.program();
+        let mut words: Vec<String> = match (&self.kind, agent) {
+            (InvocationKind::Run(task), Agent::Npm | Agent::Bun) => {
+                vec![program.to_string(), "run".to_string(), task.clone()]
+            }
+            (InvocationKind::Run(task), _) if BUILTIN_COMMANDS.contains(&task.as_str()) => {
+                vec![program.to_string(), "run".to_string(), task.clone()]
+            }
+            (InvocationKind::Run(task), _) => vec![program.to_string(), task.clone()],
+            (InvocationKind::Install { frozen: true }, Agent::Npm) => {
+                vec!["npm".to_string(), "ci".to_string()]
+            }
+            (InvocationKind::Install { frozen }, _) => {
+                let mut words = vec![program.to_string(), "install".to_string()];
+                if *frozen {
+                    words.push(agent.frozen_lockfile_flag(agent_version).to_string());
+                }
+                words
+            }
+            (InvocationKind::Exec(binary), Agent::Npm) => vec!["npx".to_string(), binary.clone()],
+            (InvocationKind::Exec(binary), Agent::Bun) => vec!["bunx".to_string(), binary.clone()],
+            (InvocationKind::Exec(binary), Agent::Pnpm) => {
+                vec!["pnpm".to_string(), "exec".to_string(), binary.clone()]
+            }
+            (InvocationKind::Exec(binary), _) if BUILTIN_COMMANDS.contains(&binary.as_str()) => {
+                vec![program.to_string(), "run".to_string(), binary.clone()]
+            }
+            (InvocationKind::Exec(binary), _) => vec![program.to_string(), binary.clone()],
+            (InvocationKind::Dlx { package, binary }, _) => {
+                let mut words = match agent {
+                    Agent::Npm => vec!["npx".to_string()],
+                    Agent::Bun => vec!["bunx".to_string()],
+                    _ => vec![program.to_string(), "dlx".to_string()],
+                };
+                if let Some(package) = package {
+                    words.push(format!("--package={package}"));
+                }
+                words.push(binary.clone());
+                words
+            }
+        };
+
+        if !self.args.is_empty() {
+            // npm passes arguments to scripts only after `--`
+            if agent == &Agent::Npm && matches!(self.kind, InvocationKind::Run(_)) {
+                words.push("--".to_string());
+            }
+            words.extend(self.args.iter().cloned());
+        }
+
+        words.join(" ")
+    }
+
+    /// Same call made with ny. Frozen installs and running binaries have no counterpart
+    pub fn ny_command(&self) -> Option<String> {
+        let mut words = match &self.kind {
+            InvocationKind::Run(task) if is_command(task) => {
+                vec!["ny".to_string(), "run".to_string(), task.clone()]
+            }
+            InvocationKind::Run(task) => vec!["ny".to_string(), task.clone()],
+            InvocationKind::Install { frozen: false } => {
+                vec!["ny".to_string(), "install".to_string()]
+            }
+            InvocationKind::Install { frozen: true }
+            | InvocationKind::Exec(_)
+            | InvocationKind::Dlx { .. } => return None,
+        };
+        words.extend(self.args.iter().cloned());
+
+        Some(words.join(" "))
+    }
+}
+
+/// Finds package manager calls at the beginning of every command in the script (after `&&`, `;`, `|` and
+/// so on). Scripts are recognized only when they exist in `scripts`, so calls of built-in commands or
+/// binaries stay untouched
+pub fn find_invocations(script: &str, scripts: &[String]) -> Vec<Invocation> {
+    command_ranges(script)
+        .into_iter()
+        .filter_map(|range| parse_invocation(script, range, scripts))
+        .collect()
+}
+
+/// Replaces calls made with other package managers with calls made with `agent`
+pub fn rewrite_invocations(
+    script: &str,
+    scripts: &[String],
+    agent: &Agent,
+    agent_version: Option<&Version>,
+) -> String {
+    replace_invocations(script, scripts, |invocation| {
+        (&invocation.agent != agent).then(|| invocation.command_for(agent, agent_version))
+    })
+}
+
+/// Replaces package manager calls with package manager agnostic `ny` calls, where there is one
+pub fn rewrite_invocations_to_ny(script: &str, scripts: &[String]) -> String {
+    replace_invocations(script, scripts, Invocation::ny_command)
+}
+
+fn replace_invocations(
+    script: &str,
+    scripts: &[String],
+    replacement: impl Fn(&Invocation) -> Option<String>,
+) -> String {
+    let mut rewritten = script.to_string();
+    for invocation in find_invocations(script, scripts).iter().rev() {
+        if !invocation.agent_args.is_empty() {
+            continue;
+        }
+        if let Some(command) = replacement(invocation) {
+            rewritten.replace_range(invocation.range.clone(), &command);
+        }
+    }
+
+    rewritten
+}
+
+fn parse_invocation(script: &str, range: Range<usize>, scripts: &[String]) -> Option<Invocation> {
+    let words = split_words(script, range.clone());
+    // skip env variables assignments like `NODE_ENV=production yarn build`
+    let start = words
+        .iter()
+        .position(|(_, word)| !is_env_assignment(word))?;
+    let words: Vec<&str> = words[start..].iter().map(|(_, word)| *word).collect();
+    let range = words_start(script, &range, start)..range.end;
+
+    let is_script = |task: &&str| scripts.iter().any(|script| script == task);
+    let (agent, kind, args, agent_args) = match words.as_slice() {
+        ["npm", "run" | "run-script", task, args @ ..] if is_script(task) => {
+            run(Agent::Npm, task, args)
+        }
+        ["npm", "test" | "t", args @ ..] if is_script(&"test") => run(Agent::Npm, "test", args),
+        ["npm", task @ ("start" | "stop" | "restart"), args @ ..] if is_script(task) => {
+            run(Agent::Npm, task, args)
+        }
+        ["npm", "install" | "i"] => install(Agent::Npm, false),
+        ["npm", "ci"] => install(Agent::Npm, true),
+        ["npx", "--package" | "-p", package, binary, args @ ..] => {
+            dlx(Agent::Npm, Some(package), binary, args)
+        }
+        ["npx", package, binary, args @ ..] if package.starts_with("--package=") => {
+            dlx(Agent::Npm, package.strip_prefix("--package="), binary, args)
+        }
+        ["npx", binary, args @ ..] => exec(Agent::Npm, binary, args),
+
+        ["yarn"] | ["yarn", "install"] => install(Agent::Yarn, false),
+        ["yarn", "install", "--frozen-lockfile" | "--immutable"] => install(Agent::Yarn, true),
+        ["yarn", "run", task, args @ ..] if is_script(task) => run(Agent::Yarn, task, args),
+        ["yarn", "dlx", "--package" | "-p", package, binary, args @ ..] => {
+            dlx(Agent::Yarn, Some(package), binary, args)
+        }
+        ["yarn", "dlx", binary, args @ ..] => dlx(Agent::Yarn, None, binary, args),
+        ["yarn", task, args @ ..] if is_script(task) && !BUILTIN_COMMANDS.contains(task) => {
+            run(Agent::Yarn, task, args)
+        }
+
+        ["pnpm", "install" | "i"] => install(Agent::Pnpm, false),
+        ["pnpm", "install" | "i", "--frozen-lockfile"] => install(Agent::Pnpm, true),
+        ["pnpm", "run", task, args @ ..] if is_script(task) => run(Agent::Pnpm, task, args),
+        ["pnpm", "exec", binary, args @ ..] => exec(Agent::Pnpm, binary, args),
+        ["pnpm", "dlx", package, binary, args @ ..] if package.starts_with("--package=") => dlx(
+            Agent::Pnpm,
+            package.strip_prefix("--package="),
+            binary,
+            args,
+        ),
+        ["pnpm", "dlx", binary, args @ ..] => dlx(Agent::Pnpm, None, binary, args),
+        ["pnpm", task, args @ ..] if is_script(task) && !BUILTIN_COMMANDS.contains(task) => {
+            run(Agent::Pnpm, task, args)
+        }
+
+        ["bun", "install" | "i"] => install(Agent::Bun, false),
+        ["bun", "install" | "i", "--frozen-lockfile"] => install(Agent::Bun, true),
+        ["bun", "run", task, args @ ..] if is_script(task) => run(Agent::Bun, task, args),
+        ["bunx", "--package" | "-p", package, binary, args @ ..] => {
+            dlx(Agent::Bun, Some(package), binary, args)
+        }
+        ["bun", "x", binary, args @ ..] | ["bunx", binary, args @ ..] => {
+            exec(Agent::Bun, binary, args)
+        }
+        _ => None,
+    }?;
+
+    let to_strings = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+
+    Some(Invocation {
+        range,
+        agent,
+        kind,
+        args: to_strings(args),
+        agent_args: to_strings(agent_args),
+    })
+}
+
+/// Agent, kind, arguments for the task or binary and arguments for the agent itself
+type Parsed<'a> = Option<(Agent, InvocationKind, &'a [&'a str], &'a [&'a str])>;
+
+fn run<'a>(agent: Agent, task: &str, args: &'a [&'a str]) -> Parsed<'a> {
+    let kind = InvocationKind::Run(task.to_string());
+    // npm passes arguments to scripts only after `--`, anything before is its own config
+    if agent == Agent::Npm {
+        return match args.iter().position(|arg| *arg == "--") {
+            Some(separator) => Some((agent, kind, &args[separator + 1..], &args[..separator])),
+            None => Some((agent, kind, &[], args)),
+        };
+    }
+    let args = match args {
+        ["--", rest @ ..] => rest,
+        args => args,
+    };
+    Some((agent, kind, args, &[]))
+}
+
+fn install<'a>(agent: Agent, frozen: bool) -> Parsed<'a> {
+    Some((agent, InvocationKind::Install { frozen }, &[], &[]))
+}
+
+fn exec<'a>(agent: Agent, binary: &str, args: &'a [&'a str]) -> Parsed<'a> {
+    (!binary.starts_with('-')).then(|| {
+        (
+            agent,
+            InvocationKind::Exec(binary.to_string()),
+            args,
+            &[][..],
+        )
+    })
+}
+
+fn dlx<'a>(agent: Agent, package: Option<&str>, binary: &str, args: &'a [&'a str]) -> Parsed<'a> {
+    let kind = InvocationKind::Dlx {
+        package: package.map(str::to_string),
+        binary: binary.to_string(),
+    };
+    (!binary.starts_with('-')).then_some((agent, kind, args, &[]))
+}
+
+fn is_env_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+    })
+}
+
+fn words_start(script: &str, range: &Range<usize>, index: usize) -> usize {
+    split_words(script, range.clone())
+        .get(index)
+        .map(|(start, _)| *start)
+        .unwrap_or(range.start)
+}
+
+/// Ranges of simple commands separated by `&&`, `||`, `;`, `|`, `&`, parentheses and new lines, ignoring
+/// separators inside quotes
+fn command_ranges(script: &str) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut start = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for (i, c) in script.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (_, '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '&' | '|' | ';' | '(' | ')' | '\n') => {
+                ranges.push(start..i);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    ranges.push(start..script.len());
+
+    ranges
+        .into_iter()
+        .filter_map(|range| {
+            let text = &script[range.clone()];
+            let leading = text.len() - text.trim_start().len();
+            let trailing = text.len() - text.trim_end().len();
+            (leading + trailing < text.len()).then(|| range.start + leading..range.end - trailing)
+        })
+        .collect()
+}
+
+/// Words of the command with their byte offsets. Quotes are kept as they are
+fn split_words(script: &str, range: Range<usize>) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut word_start: Option<usize> = None;
+    let mut quote: Option<char> = None;
+
+    for (i, c) in script[range.clone()].char_indices() {
+        let i = range.start + i;
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, c) if c.is_whitespace() => {
+                if let Some(start) = word_start.take() {
+                    words.push((start, &script[start..i]));
+                }
+            }
+            (None, c) => {
+                if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+                word_start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(start) = word_start {
+        words.push((start, &script[start..range.end]));
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scripts() -> Vec<String> {
+        ["build", "test", "lint", "add"]
+            .iter()
+            .map(|script| script.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_invocations() {
+        let script = "NODE_ENV=production yarn build --watch && npm run test -- --ci; npx tsc -p . | yarn add zod";
+
+        assert_eq!(
+            find_invocations(script, &scripts()),
+            vec![
+                Invocation {
+                    range: 20..38,
+                    agent: Agent::Yarn,
+                    kind: InvocationKind::Run("build".to_string()),
+                    args: vec!["--watch".to_string()],
+                    agent_args: vec![],
+                },
+                Invocation {
+                    range: 42..62,
+                    agent: Agent::Npm,
+                    kind: InvocationKind::Run("test".to_string()),
+                    args: vec!["--ci".to_string()],
+                    agent_args: vec![],
+                },
+                Invocation {
+                    range: 64..76,
+                    agent: Agent::Npm,
+                    kind: InvocationKind::Exec("tsc".to_string()),
+                    args: vec!["-p".to_string(), ".".to_string()],
+                    agent_args: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_invocations_with_npm_args() {
+        let invocations = find_invocations("npm run build --silent -- --watch", &scripts());
+
+        assert_eq!(invocations[0].args, ["--watch"]);
+        assert_eq!(invocations[0].agent_args, ["--silent"]);
+    }
+
+    #[test]
+    fn test_find_dlx_invocations() {
+        let kinds = |script: &str| -> Vec<InvocationKind> {
+            find_invocations(script, &scripts())
+                .into_iter()
+                .map(|invocation| invocation.kind)
+                .collect()
+        };
+        let dlx = |package: Option<&str>| InvocationKind::Dlx {
+            package: package.map(str::to_string),
+            binary: "tsc".to_string(),
+        };
+
+        assert_eq!(
+            kinds("npx -p typescript tsc && npx --package=typescript tsc && yarn dlx tsc"),
+            vec![dlx(Some("typescript")), dlx(Some("typescript")), dlx(None)]
+        );
+        assert_eq!(
+            kinds("pnpm exec tsc"),
+            vec![InvocationKind::Exec("tsc".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_find_invocations_ignores_quotes_and_unknown_tasks() {
+        assert!(find_invocations("echo 'yarn build && npm test'", &scripts()).is_empty());
+        assert!(find_invocations("yarn unknown && pnpm why react", &scripts()).is_empty());
+    }
+
+    #[test]
+    fn test_command_for() {
+        let invocation = |kind, args: &[&str]| Invocation {
+            range: 0..0,
+            agent: Agent::Yarn,
+            kind,
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            agent_args: vec![],
+        };
+        let run = invocation(InvocationKind::Run("build".to_string()), &["--watch"]);
+        let run_builtin = invocation(InvocationKind::Run("add".to_string()), &[]);
+        let install = invocation(InvocationKind::Install { frozen: true }, &[]);
+        let exec = invocation(InvocationKind::Exec("tsc".to_string()), &["-p", "."]);
+        let dlx = invocation(
+            InvocationKind::Dlx {
+                package: Some("typescript".to_string()),
+                binary: "tsc".to_string(),
+            },
+            &[],
+        );
+
+        assert_eq!(
+            run.command_for(&Agent::Npm, None),
+            "npm run build -- --watch"
+        );
+        assert_eq!(run.command_for(&Agent::Pnpm, None), "pnpm build --watch");
+        assert_eq!(run.command_for(&Agent::Bun, None), "bun run build --watch");
+        assert_eq!(run_builtin.command_for(&Agent::Pnpm, None), "pnpm run add");
+        assert_eq!(install.command_for(&Agent::Npm, None), "npm ci");
+        assert_eq!(
+            install.command_for(&Agent::Yarn, None),
+            "yarn install --immutable"
+        );
+        assert_eq!(
+            install.command_for(&Agent::Yarn, Version::parse("1.22.19").as_ref()),
+            "yarn install --frozen-lockfile"
+        );
+        assert_eq!(exec.command_for(&Agent::Npm, None), "npx tsc -p .");
+        assert_eq!(exec.command_for(&Agent::Bun, None), "bunx tsc -p .");
+        assert_eq!(exec.command_for(&Agent::Pnpm, None), "pnpm exec tsc -p .");
+        assert_eq!(exec.command_for(&Agent::Yarn, None), "yarn tsc -p .");
+        assert_eq!(
+            dlx.command_for(&Agent::Pnpm, None),
+            "pnpm dlx --package=typescript tsc"
+        );
+        assert_eq!(
+            dlx.command_for(&Agent::Npm, None),
+            "npx --package=typescript tsc"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_invocations_to_ny() {
+        assert_eq!(
+            rewrite_invocations_to_ny(
+                "yarn && npm run lint -- --fix && npm run add && npm ci && npx tsc",
+                &scripts()
+            ),
+            "ny install && ny lint --fix && ny run add && npm ci && npx tsc"
+        );
+        // `--watch` is npm's own config here, not an argument of the script
+        assert_eq!(
+            rewrite_invocations_to_ny("npm run build --watch && npm test", &scripts()),
+            "npm run build --watch && ny test"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_invocations() {
+        assert_eq!(
+            rewrite_invocations(
+                "yarn build && npm run lint -- --fix && (pnpm test)",
+                &scripts(),
+                &Agent::Bun,
+                None
+            ),
+            "bun run build && bun run lint --fix && (bun run test)"
+        );
+        assert_eq!(
+            rewrite_invocations(
+                "npm run build --silent -- --watch",
+                &scripts(),
+                &Agent::Pnpm,
+                None
+            ),
+            "npm run build --silent -- --watch"
+        );
+        assert_eq!(
+            rewrite_invocations("yarn build && yarn add zod", &scripts(), &Agent::Yarn, None),
+            "yarn build && yarn add zod"
+        );
+    }
+}
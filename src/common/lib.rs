@@ -2,9 +2,19 @@
 extern crate lazy_static;
 
 pub mod agent;
+pub mod bootstrap;
 pub mod cli;
 pub mod commands;
+pub mod config;
 pub mod execute;
 pub mod fs;
 pub mod http;
+pub mod invocations;
+pub mod lockfile;
+pub mod manifest;
+pub mod manifest_editor;
+pub mod node_version;
+pub mod npmrc;
+pub mod semver;
+pub mod tsconfig;
 mod utils;
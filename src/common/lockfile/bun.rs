@@ -0,0 +1,215 @@
+use eyre::Result;
+use std::collections::BTreeMap;
+
+use super::{split_name_version, string_entries, LockedPackage, Lockfile};
+
+const DEPENDENCY_GROUPS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "optionalDependencies",
+    "peerDependencies",
+];
+
+/// Text `bun.lock` (JSON with trailing commas). Packages are keyed by their path in node_modules, ex.
+/// `react-dom/scheduler` for scheduler installed inside react-dom, and described as
+/// `["name@version", registry, { dependencies }, integrity]`
+pub fn parse(contents: &str) -> Result<Lockfile> {
+    let lock: serde_json::Value = serde_json::from_str(&strip_trailing_commas(contents))?;
+    let empty = serde_json::Map::new();
+    let packages = lock
+        .get("packages")
+        .and_then(|packages| packages.as_object())
+        .unwrap_or(&empty);
+
+    let version_at = |key: &str| -> Option<String> {
+        let ident = packages.get(key)?.get(0)?.as_str()?;
+        let (_, version) = split_name_version(ident)?;
+        Some(version.to_string())
+    };
+    // closest copy of the package: nested in the parent first, then in parent's parents
+    let resolve = |parent: &[&str], name: &str| -> Option<String> {
+        (0..=parent.len())
+            .rev()
+            .find_map(|depth| version_at(&[&parent[..depth], &[name]].concat().join("/")))
+    };
+
+    let mut lockfile = Lockfile::default();
+    let workspaces = lock.get("workspaces").and_then(|w| w.as_object());
+    for (path, workspace) in workspaces.into_iter().flatten() {
+        let workspace_name = workspace.get("name").and_then(|name| name.as_str());
+        let parent: Vec<&str> = workspace_name
+            .filter(|_| !path.is_empty())
+            .into_iter()
+            .collect();
+        let dependencies = DEPENDENCY_GROUPS
+            .iter()
+            .flat_map(|group| string_entries(workspace.get(*group)))
+            .filter_map(|(name, _)| Some((name.clone(), resolve(&parent, &name)?)))
+            .collect();
+        lockfile.importers.insert(path.clone(), dependencies);
+    }
+
+    for (key, entry) in packages {
+        let Some((name, version)) = entry
+            .get(0)
+            .and_then(|ident| ident.as_str())
+            .and_then(split_name_version)
+        else {
+            continue;
+        };
+        if version.starts_with("workspace:") {
+            continue;
+        }
+        let info = entry.get(2).filter(|info| info.is_object());
+        let parent = split_key(key);
+        let dependencies: BTreeMap<String, String> = DEPENDENCY_GROUPS
+            .iter()
+            .flat_map(|group| string_entries(info.and_then(|info| info.get(*group))))
+            .filter_map(|(dependency, _)| {
+                Some((dependency.clone(), resolve(&parent, &dependency)?))
+            })
+            .collect();
+
+        lockfile.add(LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            resolved: entry
+                .get(1)
+                .and_then(|registry| registry.as_str())
+                .filter(|registry| !registry.is_empty())
+                .map(String::from),
+            integrity: entry
+                .as_array()
+                .and_then(|entry| entry.last())
+                .and_then(|integrity| integrity.as_str())
+                .filter(|integrity| integrity.starts_with("sha"))
+                .map(String::from),
+            dependencies,
+        });
+    }
+
+    Ok(lockfile)
+}
+
+/// Package names in the key, scoped names take two segments: `@scope/a/b` -> `["@scope/a", "b"]`
+fn split_key(key: &str) -> Vec<&str> {
+    let mut names = vec![];
+    let mut rest = key;
+    while !rest.is_empty() {
+        let separator = if rest.starts_with('@') {
+            rest.match_indices('/').nth(1).map(|(i, _)| i)
+        } else {
+            rest.find('/')
+        };
+        match separator {
+            Some(i) => {
+                names.push(&rest[..i]);
+                rest = &rest[i + 1..];
+            }
+            None => {
+                names.push(rest);
+                rest = "";
+            }
+        }
+    }
+
+    names
+}
+
+/// Removes commas followed by closing brackets, outside of strings
+fn strip_trailing_commas(contents: &str) -> String {
+    let mut result = String::with_capacity(contents.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let chars: Vec<char> = contents.chars().collect();
+
+    for (i, c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if *c == '"' {
+            in_string = true;
+        } else if *c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        result.push(*c);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let lockfile = parse(
+            r#"{
+  "lockfileVersion": 1,
+  "workspaces": {
+    "": {
+      "name": "app",
+      "dependencies": {
+        "react-dom": "^18.2.0",
+      },
+    },
+    "packages/lib": {
+      "name": "lib",
+      "dependencies": {
+        "scheduler": "^0.20.0",
+      },
+    },
+  },
+  "packages": {
+    "lib": ["lib@workspace:packages/lib"],
+    "react-dom": ["react-dom@18.2.0", "", { "dependencies": { "scheduler": "^0.23.0" }, "peerDependencies": { "react": "^18.2.0" } }, "sha512-a"],
+    "scheduler": ["scheduler@0.23.0", "", {}, "sha512-b"],
+    "lib/scheduler": ["scheduler@0.20.2", "", {}, "sha512-c"],
+  }
+}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile.importers[""],
+            BTreeMap::from([("react-dom".to_string(), "18.2.0".to_string())])
+        );
+        assert_eq!(
+            lockfile.importers["packages/lib"],
+            BTreeMap::from([("scheduler".to_string(), "0.20.2".to_string())])
+        );
+        let react_dom = lockfile.get("react-dom", "18.2.0").unwrap();
+        assert_eq!(react_dom.integrity.as_deref(), Some("sha512-a"));
+        assert_eq!(
+            react_dom.dependencies,
+            BTreeMap::from([("scheduler".to_string(), "0.23.0".to_string())])
+        );
+        assert_eq!(lockfile.packages.len(), 3);
+    }
+
+    #[test]
+    fn test_split_key() {
+        assert_eq!(split_key("react-dom/scheduler"), ["react-dom", "scheduler"]);
+        assert_eq!(
+            split_key("@scope/a/@scope/b/c"),
+            ["@scope/a", "@scope/b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_strip_trailing_commas() {
+        assert_eq!(
+            strip_trailing_commas("{ \"a\": [1, 2, ], \"b,\": \"}\", }"),
+            "{ \"a\": [1, 2 ], \"b,\": \"}\" }"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use eyre::{eyre, Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::fs::Filesystem;
+
+pub mod bun;
+pub mod npm;
+pub mod pnpm;
+pub mod yarn;
+
+/// Package resolved in a lockfile. The same name and version is listed only once
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    /// tarball url or other resolution, ex. `react@npm:18.2.0` for yarn berry
+    pub resolved: Option<String>,
+    pub integrity: Option<String>,
+    /// names of dependencies and versions they resolve to
+    pub dependencies: BTreeMap<String, String>,
+}
+
+/// Resolved dependency graph read from a lockfile of any supported package manager
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Lockfile {
+    /// direct dependencies of projects (keyed by path relative to the lockfile, "" for the root) and versions
+    /// they resolve to. Empty for lockfiles that don't record them (yarn v1)
+    pub importers: BTreeMap<String, BTreeMap<String, String>>,
+    pub packages: Vec<LockedPackage>,
+}
+
+impl Lockfile {
+    /// Picks the parser by file name
+    pub fn load(fs: &dyn Filesystem, path: &Path) -> Result<Self> {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        if file_name == "bun.lockb" {
+            return Err(eyre!(
+                "Binary bun.lockb can't be read, run `bun install --save-text-lockfile` to create bun.lock"
+            ));
+        }
+        let parse = match file_name {
+            "package-lock.json" | "npm-shrinkwrap.json" => npm::parse,
+            "yarn.lock" => yarn::parse,
+            "pnpm-lock.yaml" => pnpm::parse,
+            "bun.lock" => bun::parse,
+            _ => return Err(eyre!("Unknown lockfile: {path:?}")),
+        };
+        let contents = fs
+            .read_to_string(path)
+            .with_context(|| format!("Couldn't read {path:?}"))?;
+
+        parse(&contents).with_context(|| format!("Couldn't parse {path:?}"))
+    }
+
+    /// All versions of the package
+    pub fn find(&self, name: &str) -> Vec<&LockedPackage> {
+        self.packages
+            .iter()
+            .filter(|package| package.name == name)
+            .collect()
+    }
+
+    pub fn get(&self, name: &str, version: &str) -> Option<&LockedPackage> {
+        self.packages
+            .iter()
+            .find(|package| package.name == name && package.version == version)
+    }
+
+    /// Packages depending on the given version of the package
+    pub fn dependents(&self, name: &str, version: &str) -> Vec<&LockedPackage> {
+        self.packages
+            .iter()
+            .filter(|package| package.dependencies.get(name).map(String::as_str) == Some(version))
+            .collect()
+    }
+
+    /// Adds the package unless the same name and version is already there
+    fn add(&mut self, package: LockedPackage) {
+        if self.get(&package.name, &package.version).is_none() {
+            self.packages.push(package);
+        }
+    }
+}
+
+/// Splits `name@version` keeping `@` of scoped packages in the name
+fn split_name_version(ident: &str) -> Option<(&str, &str)> {
+    let at = ident
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == '@')
+        .map(|(at, _)| at)?;
+
+    Some((&ident[..at], &ident[at + 1..]))
+}
+
+/// String value, YAML parsers turn unquoted versions like `1` into numbers
+fn as_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn string_field(value: &serde_json::Value, key: &str) -> Option<String> {
+    value.get(key).and_then(as_text)
+}
+
+fn string_entries(value: Option<&serde_json::Value>) -> Vec<(String, String)> {
+    value
+        .and_then(|value| value.as_object())
+        .map(|object| {
+            object
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), as_text(value)?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+
+    use super::*;
+
+    fn package(name: &str, version: &str, dependencies: &[(&str, &str)]) -> LockedPackage {
+        LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            resolved: None,
+            integrity: None,
+            dependencies: dependencies
+                .iter()
+                .map(|(name, version)| (name.to_string(), version.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_queries() {
+        let lockfile = Lockfile {
+            importers: BTreeMap::new(),
+            packages: vec![
+                package("react-dom", "18.2.0", &[("scheduler", "0.23.0")]),
+                package("scheduler", "0.23.0", &[]),
+                package("scheduler", "0.20.0", &[]),
+            ],
+        };
+
+        assert_eq!(lockfile.find("scheduler").len(), 2);
+        assert_eq!(
+            lockfile.get("scheduler", "0.20.0"),
+            Some(&package("scheduler", "0.20.0", &[]))
+        );
+        assert_eq!(
+            lockfile.dependents("scheduler", "0.23.0"),
+            vec![&lockfile.packages[0]]
+        );
+        assert!(lockfile.dependents("scheduler", "0.20.0").is_empty());
+    }
+
+    #[test]
+    fn test_load() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/bun.lock").to_owned(),
+            r#"{ "lockfileVersion": 1, "workspaces": {}, "packages": { "zod": ["zod@3.23.8", "", {}, "sha512-x"], }, }"#
+                .to_owned(),
+        );
+
+        let lockfile = Lockfile::load(&mock_fs, Path::new("/project/bun.lock")).unwrap();
+        assert_eq!(lockfile.packages.len(), 1);
+        assert!(Lockfile::load(&mock_fs, Path::new("/project/bun.lockb")).is_err());
+    }
+
+    #[test]
+    fn test_split_name_version() {
+        assert_eq!(split_name_version("zod@3.0.0"), Some(("zod", "3.0.0")));
+        assert_eq!(
+            split_name_version("@scope/a@npm:1.0.0"),
+            Some(("@scope/a", "npm:1.0.0"))
+        );
+        assert_eq!(split_name_version("@scope/a"), None);
+    }
+}
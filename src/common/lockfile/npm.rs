@@ -0,0 +1,211 @@
+use eyre::Result;
+use std::collections::BTreeMap;
+
+use super::{string_entries, string_field, LockedPackage, Lockfile};
+
+const DEPENDENCY_GROUPS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "optionalDependencies",
+    "peerDependencies",
+];
+
+type Entries = serde_json::Map<String, serde_json::Value>;
+
+/// `package-lock.json` and `npm-shrinkwrap.json`. v2 and v3 list installed packages by their path in
+/// `packages`, v1 has only nested `dependencies` and doesn't record direct dependencies of the project so all
+/// top level packages are treated as such
+pub fn parse(contents: &str) -> Result<Lockfile> {
+    let lock: serde_json::Value = serde_json::from_str(contents)?;
+
+    Ok(match lock.get("packages").and_then(|p| p.as_object()) {
+        Some(packages) => parse_packages(packages),
+        None => {
+            let mut lockfile = Lockfile::default();
+            if let Some(dependencies) = lock.get("dependencies").and_then(|d| d.as_object()) {
+                let top_level = dependencies
+                    .iter()
+                    .filter_map(|(name, entry)| {
+                        Some((name.clone(), string_field(entry, "version")?))
+                    })
+                    .collect();
+                lockfile.importers.insert(String::new(), top_level);
+                parse_dependencies(dependencies, &mut vec![], &mut lockfile);
+            }
+            lockfile
+        }
+    })
+}
+
+fn parse_packages(entries: &Entries) -> Lockfile {
+    let mut lockfile = Lockfile::default();
+
+    for (path, entry) in entries {
+        let dependencies = || -> BTreeMap<String, String> {
+            DEPENDENCY_GROUPS
+                .iter()
+                .flat_map(|group| string_entries(entry.get(*group)))
+                .filter_map(|(name, _)| Some((name.clone(), resolve(entries, path, &name)?)))
+                .collect()
+        };
+
+        let Some(name_start) = path
+            .rfind("node_modules/")
+            .map(|i| i + "node_modules/".len())
+        else {
+            // root project and workspaces
+            lockfile.importers.insert(path.clone(), dependencies());
+            continue;
+        };
+        if entry.get("link").and_then(|link| link.as_bool()) == Some(true) {
+            continue;
+        }
+        let Some(version) = string_field(entry, "version") else {
+            continue;
+        };
+
+        lockfile.add(LockedPackage {
+            // aliased packages (`npm:` specs) record real name
+            name: string_field(entry, "name").unwrap_or_else(|| path[name_start..].to_string()),
+            version,
+            resolved: string_field(entry, "resolved"),
+            integrity: string_field(entry, "integrity"),
+            dependencies: dependencies(),
+        });
+    }
+
+    lockfile
+}
+
+/// Node.js module resolution: `node_modules` of the package itself first, then of its parents
+fn resolve(entries: &Entries, from: &str, name: &str) -> Option<String> {
+    let mut dir = from;
+    loop {
+        let key = if dir.is_empty() {
+            format!("node_modules/{name}")
+        } else {
+            format!("{dir}/node_modules/{name}")
+        };
+        if let Some(entry) = entries.get(&key) {
+            return match string_field(entry, "resolved") {
+                // links point at workspaces
+                Some(target) if entry.get("link").is_some() => entries
+                    .get(&target)
+                    .and_then(|t| string_field(t, "version")),
+                _ => string_field(entry, "version"),
+            };
+        }
+        if dir.is_empty() {
+            return None;
+        }
+        dir = dir.rfind("/node_modules/").map_or("", |i| &dir[..i]);
+    }
+}
+
+fn parse_dependencies<'a>(
+    dependencies: &'a Entries,
+    scopes: &mut Vec<&'a Entries>,
+    lockfile: &mut Lockfile,
+) {
+    scopes.push(dependencies);
+    for (name, entry) in dependencies {
+        let Some(version) = string_field(entry, "version") else {
+            continue;
+        };
+        let nested = entry.get("dependencies").and_then(|d| d.as_object());
+        let resolved_dependencies = string_entries(entry.get("requires"))
+            .into_iter()
+            .filter_map(|(required, _)| {
+                let version = nested
+                    .into_iter()
+                    .chain(scopes.iter().rev().copied())
+                    .find_map(|scope| scope.get(&required))
+                    .and_then(|entry| string_field(entry, "version"))?;
+                Some((required, version))
+            })
+            .collect();
+
+        lockfile.add(LockedPackage {
+            name: name.clone(),
+            version,
+            resolved: string_field(entry, "resolved"),
+            integrity: string_field(entry, "integrity"),
+            dependencies: resolved_dependencies,
+        });
+        if let Some(nested) = nested {
+            parse_dependencies(nested, scopes, lockfile);
+        }
+    }
+    scopes.pop();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_v3() {
+        let lockfile = parse(
+            r#"{
+                "name": "app", "lockfileVersion": 3,
+                "packages": {
+                    "": { "name": "app", "workspaces": ["packages/*"], "dependencies": { "react-dom": "^18" }, "devDependencies": { "lib": "*" } },
+                    "packages/lib": { "name": "lib", "version": "1.0.0", "dependencies": { "scheduler": "^0.20" } },
+                    "node_modules/lib": { "resolved": "packages/lib", "link": true },
+                    "node_modules/react-dom": { "version": "18.2.0", "resolved": "https://registry.npmjs.org/react-dom/-/react-dom-18.2.0.tgz", "integrity": "sha512-a", "dependencies": { "scheduler": "^0.23.0" } },
+                    "node_modules/scheduler": { "version": "0.20.2" },
+                    "node_modules/react-dom/node_modules/scheduler": { "version": "0.23.0" }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile.importers.get(""),
+            Some(&BTreeMap::from([
+                ("lib".to_string(), "1.0.0".to_string()),
+                ("react-dom".to_string(), "18.2.0".to_string())
+            ]))
+        );
+        assert_eq!(
+            lockfile.importers["packages/lib"].get("scheduler"),
+            Some(&"0.20.2".to_string())
+        );
+        let react_dom = lockfile.get("react-dom", "18.2.0").unwrap();
+        assert_eq!(react_dom.integrity.as_deref(), Some("sha512-a"));
+        assert_eq!(
+            react_dom.dependencies.get("scheduler"),
+            Some(&"0.23.0".to_string())
+        );
+        assert_eq!(lockfile.find("scheduler").len(), 2);
+    }
+
+    #[test]
+    fn test_parse_v1() {
+        let lockfile = parse(
+            r#"{
+                "lockfileVersion": 1,
+                "dependencies": {
+                    "react-dom": {
+                        "version": "18.2.0",
+                        "requires": { "scheduler": "^0.23.0", "loose-envify": "^1.1.0" },
+                        "dependencies": { "scheduler": { "version": "0.23.0" } }
+                    },
+                    "loose-envify": { "version": "1.4.0" },
+                    "scheduler": { "version": "0.20.2", "requires": { "loose-envify": "^1.1.0" } }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile.get("react-dom", "18.2.0").unwrap().dependencies,
+            BTreeMap::from([
+                ("loose-envify".to_string(), "1.4.0".to_string()),
+                ("scheduler".to_string(), "0.23.0".to_string())
+            ])
+        );
+        assert_eq!(lockfile.importers[""].len(), 3);
+        assert_eq!(lockfile.packages.len(), 4);
+    }
+}
@@ -0,0 +1,316 @@
+use eyre::Result;
+use std::collections::BTreeMap;
+
+use super::{as_text, split_name_version, string_entries, LockedPackage, Lockfile};
+
+const DEPENDENCY_GROUPS: &[&str] = &["dependencies", "devDependencies", "optionalDependencies"];
+
+/// `pnpm-lock.yaml` v5 to v9. Packages are keyed `/name/version` (v5), `/name@version` (v6) or
+/// `name@version` (v9, with dependencies moved to `snapshots`). Versions may carry peer dependencies
+/// suffix, ex. `18.2.0_react@18.2.0` or `18.2.0(react@18.2.0)`, which is dropped
+pub fn parse(contents: &str) -> Result<Lockfile> {
+    let lock: serde_json::Value = serde_yaml::from_str(contents)?;
+    let major_version = lock
+        .get("lockfileVersion")
+        .and_then(as_text)
+        .and_then(|version| version.split('.').next()?.parse::<u32>().ok())
+        .unwrap_or(9);
+    let is_v5 = major_version < 6;
+
+    let mut lockfile = Lockfile::default();
+    match lock
+        .get("importers")
+        .and_then(|importers| importers.as_object())
+    {
+        Some(importers) => {
+            for (path, importer) in importers {
+                let path = if path == "." { "" } else { path.as_str() };
+                lockfile
+                    .importers
+                    .insert(path.to_string(), importer_dependencies(importer, is_v5));
+            }
+        }
+        None => {
+            lockfile
+                .importers
+                .insert(String::new(), importer_dependencies(&lock, is_v5));
+        }
+    }
+
+    let packages = lock
+        .get("packages")
+        .and_then(|packages| packages.as_object());
+    // since v9 `packages` only describe tarballs, dependencies are in `snapshots`
+    let graph = lock
+        .get("snapshots")
+        .and_then(|snapshots| snapshots.as_object())
+        .or(packages);
+    for (key, snapshot) in graph.into_iter().flatten() {
+        let Some((name, version)) = parse_package_key(key, is_v5) else {
+            continue;
+        };
+        let metadata = packages
+            .and_then(|packages| {
+                packages
+                    .get(key)
+                    .or_else(|| packages.get(&format!("{name}@{version}")))
+            })
+            .unwrap_or(snapshot);
+        let resolution = metadata.get("resolution");
+
+        lockfile.add(LockedPackage {
+            dependencies: ["dependencies", "optionalDependencies"]
+                .iter()
+                .flat_map(|group| string_entries(snapshot.get(*group)))
+                .filter_map(|(name, reference)| {
+                    let version = resolve_reference(&reference, is_v5)?;
+                    Some((name, version))
+                })
+                .collect(),
+            resolved: resolution
+                .and_then(|resolution| resolution.get("tarball"))
+                .and_then(as_text),
+            integrity: resolution
+                .and_then(|resolution| resolution.get("integrity"))
+                .and_then(as_text),
+            name,
+            version,
+        });
+    }
+
+    Ok(lockfile)
+}
+
+/// Importer dependencies are plain versions (v5) or `{ specifier, version }` objects (v6+)
+fn importer_dependencies(importer: &serde_json::Value, is_v5: bool) -> BTreeMap<String, String> {
+    DEPENDENCY_GROUPS
+        .iter()
+        .filter_map(|group| importer.get(*group)?.as_object())
+        .flatten()
+        .filter_map(|(name, dependency)| {
+            let reference = dependency
+                .get("version")
+                .and_then(as_text)
+                .or_else(|| as_text(dependency))?;
+            Some((name.clone(), resolve_reference(&reference, is_v5)?))
+        })
+        .collect()
+}
+
+/// Version from a dependency reference. Aliases point at another package (`/string-width/4.2.3` or
+/// `string-width@4.2.3`), links to workspaces are skipped
+fn resolve_reference(reference: &str, is_v5: bool) -> Option<String> {
+    if reference.starts_with("link:") || reference.starts_with("file:") {
+        return None;
+    }
+    let without_peers = strip_peers(reference, is_v5);
+    if reference.starts_with('/') || split_name_version(without_peers).is_some() {
+        return parse_package_key(reference, is_v5).map(|(_, version)| version);
+    }
+
+    Some(without_peers.to_string())
+}
+
+fn parse_package_key(key: &str, is_v5: bool) -> Option<(String, String)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    let (name, version) = if is_v5 {
+        // name is one segment or two when scoped, v5 peer suffix after the version may contain slashes
+        let name_end = if key.starts_with('@') {
+            key.match_indices('/').nth(1)?.0
+        } else {
+            key.find('/')?
+        };
+        (&key[..name_end], &key[name_end + 1..])
+    } else {
+        split_name_version(key)?
+    };
+
+    Some((name.to_string(), strip_peers(version, is_v5).to_string()))
+}
+
+/// Drops peer dependencies suffix of the version, `_react@18.2.0` (v5) or `(react@18.2.0)` (v6+)
+fn strip_peers(version: &str, is_v5: bool) -> &str {
+    let separator = if is_v5 { '_' } else { '(' };
+    version.split(separator).next().unwrap_or(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_v9() {
+        let lockfile = parse(
+            r#"lockfileVersion: '9.0'
+
+importers:
+
+  .:
+    dependencies:
+      react-dom:
+        specifier: ^18.2.0
+        version: 18.2.0(react@18.2.0)
+      lib:
+        specifier: workspace:*
+        version: link:packages/lib
+    devDependencies:
+      cowsay:
+        specifier: npm:cowsay2@^2
+        version: cowsay2@2.0.4
+      merge:
+        specifier: npm:lodash_merge@^1
+        version: lodash_merge@1.0.0
+
+  packages/lib:
+    dependencies:
+      react:
+        specifier: ^18
+        version: 18.2.0
+
+packages:
+
+  cowsay2@2.0.4:
+    resolution: {integrity: sha512-c}
+
+  lodash_merge@1.0.0:
+    resolution: {integrity: sha512-d}
+
+  react-dom@18.2.0:
+    resolution: {integrity: sha512-a}
+    peerDependencies:
+      react: ^18.2.0
+
+  react@18.2.0:
+    resolution: {integrity: sha512-b}
+
+snapshots:
+
+  cowsay2@2.0.4: {}
+
+  lodash_merge@1.0.0: {}
+
+  react-dom@18.2.0(react@18.2.0):
+    dependencies:
+      react: 18.2.0
+
+  react@18.2.0: {}
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile.importers[""],
+            BTreeMap::from([
+                ("cowsay".to_string(), "2.0.4".to_string()),
+                ("merge".to_string(), "1.0.0".to_string()),
+                ("react-dom".to_string(), "18.2.0".to_string())
+            ])
+        );
+        assert_eq!(
+            lockfile.importers["packages/lib"].get("react"),
+            Some(&"18.2.0".to_string())
+        );
+        let react_dom = lockfile.get("react-dom", "18.2.0").unwrap();
+        assert_eq!(react_dom.integrity.as_deref(), Some("sha512-a"));
+        assert_eq!(
+            react_dom.dependencies,
+            BTreeMap::from([("react".to_string(), "18.2.0".to_string())])
+        );
+        assert!(lockfile.get("lodash_merge", "1.0.0").is_some());
+        assert_eq!(lockfile.packages.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_v6() {
+        let lockfile = parse(
+            r#"lockfileVersion: '6.0'
+
+dependencies:
+  '@scope/a':
+    specifier: ^1.0.0
+    version: 1.0.0(react@18.2.0)
+
+packages:
+
+  /@scope/a@1.0.0(react@18.2.0):
+    resolution: {integrity: sha512-a}
+    dependencies:
+      react: 18.2.0
+    dev: false
+
+  /react@18.2.0:
+    resolution: {integrity: sha512-b}
+    dev: false
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile.importers[""].get("@scope/a"),
+            Some(&"1.0.0".to_string())
+        );
+        assert_eq!(
+            lockfile.get("@scope/a", "1.0.0").unwrap().dependencies,
+            BTreeMap::from([("react".to_string(), "18.2.0".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_parse_v5() {
+        let lockfile = parse(
+            r#"lockfileVersion: 5.4
+
+specifiers:
+  '@scope/a': ^1.0.0
+  string_decoder: ^1.3.0
+
+dependencies:
+  '@scope/a': 1.0.0_react@18.2.0
+  string_decoder: 1.3.0
+
+packages:
+
+  /@scope/a/1.0.0_react@18.2.0:
+    resolution: {integrity: sha512-a}
+    dependencies:
+      react: 18.2.0
+      sd: /string_decoder/1.3.0
+    dev: false
+
+  /string_decoder/1.3.0:
+    resolution: {integrity: sha512-c}
+    dev: false
+
+  /react/18.2.0:
+    resolution: {integrity: sha512-b}
+    dev: false
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile.importers[""].get("@scope/a"),
+            Some(&"1.0.0".to_string())
+        );
+        assert_eq!(
+            lockfile.importers[""].get("string_decoder"),
+            Some(&"1.3.0".to_string())
+        );
+        assert_eq!(
+            lockfile.get("@scope/a", "1.0.0").unwrap().dependencies,
+            BTreeMap::from([
+                ("react".to_string(), "18.2.0".to_string()),
+                ("sd".to_string(), "1.3.0".to_string())
+            ])
+        );
+        assert!(lockfile.get("string_decoder", "1.3.0").is_some());
+        assert_eq!(
+            lockfile
+                .get("react", "18.2.0")
+                .unwrap()
+                .integrity
+                .as_deref(),
+            Some("sha512-b")
+        );
+    }
+}
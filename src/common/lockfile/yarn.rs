@@ -0,0 +1,299 @@
+use eyre::{eyre, Result};
+use std::collections::BTreeMap;
+
+use super::{split_name_version, string_entries, string_field, LockedPackage, Lockfile};
+
+const DEPENDENCY_GROUPS: &[&str] = &["dependencies", "optionalDependencies", "peerDependencies"];
+
+/// `yarn.lock` of yarn v1 (custom format) and yarn v2+ (YAML with `__metadata` key)
+pub fn parse(contents: &str) -> Result<Lockfile> {
+    if contents.lines().any(|line| line.starts_with("__metadata:")) {
+        parse_berry(contents)
+    } else {
+        parse_classic(contents)
+    }
+}
+
+/// Entry of the lockfile before dependency ranges are resolved
+struct Entry {
+    /// `name@range` pairs resolving to this entry
+    descriptors: Vec<String>,
+    name: String,
+    version: String,
+    resolved: Option<String>,
+    integrity: Option<String>,
+    /// names and ranges
+    dependencies: Vec<(String, String)>,
+}
+
+fn parse_classic(contents: &str) -> Result<Lockfile> {
+    let mut entries: Vec<Entry> = vec![];
+    let mut in_dependencies = false;
+
+    for (i, line) in contents.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let invalid = || eyre!("Unexpected content at line {}: {line}", i + 1);
+
+        if indent == 0 {
+            let descriptors: Vec<String> = trimmed
+                .strip_suffix(':')
+                .ok_or_else(invalid)?
+                .split(", ")
+                .map(|descriptor| unquote(descriptor).to_string())
+                .collect();
+            let (name, _) = descriptors
+                .first()
+                .and_then(|descriptor| split_name_version(descriptor))
+                .ok_or_else(invalid)?;
+            entries.push(Entry {
+                name: name.to_string(),
+                descriptors,
+                version: String::new(),
+                resolved: None,
+                integrity: None,
+                dependencies: vec![],
+            });
+            in_dependencies = false;
+            continue;
+        }
+
+        let entry = entries.last_mut().ok_or_else(invalid)?;
+        if let Some(section) = trimmed.strip_suffix(':').filter(|_| indent == 2) {
+            in_dependencies = DEPENDENCY_GROUPS.contains(&section);
+            continue;
+        }
+        let (key, value) = split_key_value(trimmed).ok_or_else(invalid)?;
+        if indent > 2 {
+            if in_dependencies {
+                entry.dependencies.push((key, value));
+            }
+            continue;
+        }
+        match key.as_str() {
+            "version" => entry.version = value,
+            "resolved" => entry.resolved = Some(value),
+            "integrity" => entry.integrity = Some(value),
+            _ => {}
+        }
+    }
+
+    Ok(resolve_entries(entries, &[""]))
+}
+
+fn parse_berry(contents: &str) -> Result<Lockfile> {
+    let lock: serde_json::Value = serde_yaml::from_str(contents)?;
+    let mut entries = vec![];
+
+    for (key, value) in lock.as_object().into_iter().flatten() {
+        if key == "__metadata" {
+            continue;
+        }
+        let descriptors: Vec<String> = key.split(", ").map(str::to_string).collect();
+        let Some(resolution) = string_field(value, "resolution") else {
+            continue;
+        };
+        let Some((name, _)) = split_name_version(&resolution) else {
+            continue;
+        };
+        entries.push(Entry {
+            name: name.to_string(),
+            version: string_field(value, "version").unwrap_or_default(),
+            integrity: string_field(value, "checksum"),
+            dependencies: DEPENDENCY_GROUPS
+                .iter()
+                .flat_map(|group| string_entries(value.get(*group)))
+                .collect(),
+            resolved: Some(resolution),
+            descriptors,
+        });
+    }
+
+    // dependency ranges don't mention the default protocol, descriptors do
+    Ok(resolve_entries(entries, &["", "npm:"]))
+}
+
+/// Resolves dependency ranges to versions by looking up entries with matching descriptors. Workspaces of
+/// yarn berry (resolution `name@workspace:path`) become importers
+fn resolve_entries(entries: Vec<Entry>, protocols: &[&str]) -> Lockfile {
+    let versions: BTreeMap<&str, &str> = entries
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .descriptors
+                .iter()
+                .map(|descriptor| (descriptor.as_str(), entry.version.as_str()))
+        })
+        .collect();
+    let resolve = |dependencies: &[(String, String)]| -> BTreeMap<String, String> {
+        dependencies
+            .iter()
+            .filter_map(|(name, range)| {
+                let version = protocols.iter().find_map(|protocol| {
+                    versions.get(format!("{name}@{protocol}{range}").as_str())
+                })?;
+                Some((name.clone(), version.to_string()))
+            })
+            .collect()
+    };
+
+    let mut lockfile = Lockfile::default();
+    for entry in &entries {
+        let workspace = entry
+            .resolved
+            .as_deref()
+            .and_then(split_name_version)
+            .and_then(|(_, reference)| reference.strip_prefix("workspace:"));
+        if let Some(path) = workspace {
+            let path = if path == "." { "" } else { path };
+            lockfile
+                .importers
+                .insert(path.to_string(), resolve(&entry.dependencies));
+            continue;
+        }
+
+        lockfile.add(LockedPackage {
+            name: entry.name.clone(),
+            version: entry.version.clone(),
+            resolved: entry.resolved.clone(),
+            integrity: entry.integrity.clone(),
+            dependencies: resolve(&entry.dependencies),
+        });
+    }
+
+    lockfile
+}
+
+/// `key value` or `"key" "value"` line of yarn v1 lockfile
+fn split_key_value(line: &str) -> Option<(String, String)> {
+    let (key, value) = if let Some(quoted) = line.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], quoted[end + 1..].trim_start())
+    } else {
+        line.split_once(' ')?
+    };
+
+    Some((key.to_string(), unquote(value.trim()).to_string()))
+}
+
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_classic() {
+        let lockfile = parse(
+            r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@babel/highlight@^7.10.4", "@babel/highlight@^7.12.13":
+  version "7.12.13"
+  resolved "https://registry.yarnpkg.com/@babel/highlight/-/highlight-7.12.13.tgz#8ab538393e00370b26271b01fa08f7f27f2e795c"
+  integrity sha512-kocDQvIbgMKlWxXe9fof3TQ+gkIPOUSEYhJjqUjvKMez3krV7vbzYCDq39Oj11UAVK7JqPVGQPlgE85dPNlQww==
+  dependencies:
+    "@babel/helper-validator-identifier" "^7.12.11"
+    js-tokens "^4.0.0"
+
+"@babel/helper-validator-identifier@^7.12.11":
+  version "7.12.11"
+
+js-tokens@^4.0.0:
+  version "4.0.0"
+"#,
+        )
+        .unwrap();
+
+        assert!(lockfile.importers.is_empty());
+        assert_eq!(lockfile.packages.len(), 3);
+        let highlight = lockfile.get("@babel/highlight", "7.12.13").unwrap();
+        assert!(highlight
+            .integrity
+            .as_deref()
+            .is_some_and(|integrity| integrity.starts_with("sha512-")));
+        assert_eq!(
+            highlight.dependencies,
+            BTreeMap::from([
+                (
+                    "@babel/helper-validator-identifier".to_string(),
+                    "7.12.11".to_string()
+                ),
+                ("js-tokens".to_string(), "4.0.0".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_berry() {
+        let lockfile = parse(
+            r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 8
+  cacheKey: 10c0
+
+"app@workspace:.":
+  version: 0.0.0-use.local
+  resolution: "app@workspace:."
+  dependencies:
+    react: "npm:^18.2.0"
+    lib: "workspace:*"
+  languageName: unknown
+  linkType: soft
+
+"lib@workspace:*, lib@workspace:packages/lib":
+  version: 0.0.0-use.local
+  resolution: "lib@workspace:packages/lib"
+  dependencies:
+    loose-envify: ^1
+  languageName: unknown
+  linkType: soft
+
+"loose-envify@npm:^1, loose-envify@npm:^1.1.0":
+  version: 1.4.0
+  resolution: "loose-envify@npm:1.4.0"
+  checksum: 10c0/abc
+  languageName: node
+  linkType: hard
+
+"react@npm:^18.2.0":
+  version: 18.2.0
+  resolution: "react@npm:18.2.0"
+  dependencies:
+    loose-envify: "npm:^1.1.0"
+  checksum: 10c0/def
+  languageName: node
+  linkType: hard
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            lockfile.importers[""],
+            BTreeMap::from([
+                ("lib".to_string(), "0.0.0-use.local".to_string()),
+                ("react".to_string(), "18.2.0".to_string())
+            ])
+        );
+        assert_eq!(
+            lockfile.importers["packages/lib"].get("loose-envify"),
+            Some(&"1.4.0".to_string())
+        );
+        let react = lockfile.get("react", "18.2.0").unwrap();
+        assert_eq!(react.resolved.as_deref(), Some("react@npm:18.2.0"));
+        assert_eq!(react.integrity.as_deref(), Some("10c0/def"));
+        assert_eq!(
+            react.dependencies.get("loose-envify"),
+            Some(&"1.4.0".to_string())
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use eyre::{eyre, Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::fs::Filesystem;
+
+const DEPENDENCY_GROUPS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// Typed model of package.json. Parsing is lenient: fields with unexpected types are skipped
+/// so a single odd field doesn't make the whole manifest unusable
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PackageManifest {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub scripts: BTreeMap<String, String>,
+    pub dependencies: BTreeMap<String, String>,
+    pub dev_dependencies: BTreeMap<String, String>,
+    pub peer_dependencies: BTreeMap<String, String>,
+    pub optional_dependencies: BTreeMap<String, String>,
+    /// globs from `workspaces` (array or yarn's `{ "packages": [...] }` form)
+    pub workspaces: Vec<String>,
+    pub package_manager: Option<String>,
+    pub engines: BTreeMap<String, String>,
+    pub volta: BTreeMap<String, String>,
+    /// `types` or its older alias `typings`
+    pub types: Option<String>,
+    pub exports: Option<serde_json::Value>,
+    /// names of executables, `bin` given as a string is named after the package
+    pub bin: BTreeMap<String, String>,
+    /// ny's own settings, see `crate::config::Settings`
+    pub ny: Option<serde_json::Value>,
+}
+
+impl PackageManifest {
+    pub fn load(fs: &dyn Filesystem, path: &Path) -> Result<Self> {
+        let contents = fs
+            .read_to_string(path)
+            .with_context(|| format!("Couldn't read {path:?}"))?;
+
+        Self::parse(&contents).with_context(|| format!("Couldn't parse {path:?}"))
+    }
+
+    /// Errors point at the line and column of invalid JSON
+    pub fn parse(contents: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(contents)?;
+        if !value.is_object() {
+            return Err(eyre!("expected an object at line 1 column 1"));
+        }
+
+        Ok(Self::from_value(&value))
+    }
+
+    pub fn from_value(value: &serde_json::Value) -> Self {
+        let string = |key: &str| value.get(key).and_then(as_string);
+        let dependencies = |group: &str| string_map(value.get(group));
+        let name = string("name");
+
+        Self {
+            version: string("version"),
+            scripts: string_map(value.get("scripts")),
+            dependencies: dependencies("dependencies"),
+            dev_dependencies: dependencies("devDependencies"),
+            peer_dependencies: dependencies("peerDependencies"),
+            optional_dependencies: dependencies("optionalDependencies"),
+            workspaces: parse_workspaces(value.get("workspaces")),
+            package_manager: string("packageManager"),
+            engines: string_map(value.get("engines")),
+            volta: string_map(value.get("volta")),
+            types: string("types").or_else(|| string("typings")),
+            exports: value.get("exports").cloned(),
+            bin: match value.get("bin") {
+                Some(serde_json::Value::String(path)) => name
+                    .as_ref()
+                    .map(|name| {
+                        // scoped packages get executable named without the scope
+                        let executable = name.rsplit('/').next().unwrap_or(name);
+                        BTreeMap::from([(executable.to_string(), path.clone())])
+                    })
+                    .unwrap_or_default(),
+                bin => string_map(bin),
+            },
+            ny: value.get("ny").cloned(),
+            name,
+        }
+    }
+
+    /// Names of all dependencies (regular, dev, peer and optional) without duplicates
+    pub fn dependency_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+        for group in DEPENDENCY_GROUPS {
+            for name in self.dependency_group(group).keys() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+
+        names
+    }
+
+    pub fn dependency_group(&self, group: &str) -> &BTreeMap<String, String> {
+        match group {
+            "devDependencies" => &self.dev_dependencies,
+            "peerDependencies" => &self.peer_dependencies,
+            "optionalDependencies" => &self.optional_dependencies,
+            _ => &self.dependencies,
+        }
+    }
+
+    pub fn has_dependency(&self, name: &str) -> bool {
+        DEPENDENCY_GROUPS
+            .iter()
+            .any(|group| self.dependency_group(group).contains_key(name))
+    }
+
+    /// Package ships its own typings with `types` field or `types` condition in `exports`
+    pub fn has_types(&self) -> bool {
+        self.types.is_some() || self.exports.as_ref().is_some_and(has_types_condition)
+    }
+}
+
+fn as_string(value: &serde_json::Value) -> Option<String> {
+    value.as_str().map(|value| value.to_string())
+}
+
+fn string_map(value: Option<&serde_json::Value>) -> BTreeMap<String, String> {
+    value
+        .and_then(|value| value.as_object())
+        .map(|object| {
+            object
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), as_string(value)?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_workspaces(value: Option<&serde_json::Value>) -> Vec<String> {
+    let globs = match value {
+        Some(serde_json::Value::Object(object)) => object.get("packages"),
+        value => value,
+    };
+
+    globs
+        .and_then(|globs| globs.as_array())
+        .map(|globs| globs.iter().filter_map(as_string).collect())
+        .unwrap_or_default()
+}
+
+fn has_types_condition(exports: &serde_json::Value) -> bool {
+    match exports {
+        serde_json::Value::Object(object) => object
+            .iter()
+            .any(|(key, value)| key == "types" || has_types_condition(value)),
+        serde_json::Value::Array(values) => values.iter().any(has_types_condition),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let manifest = PackageManifest::parse(
+            r#"{
+                "name": "@scope/tool",
+                "version": "1.2.3",
+                "scripts": { "test": "mocha", "broken": 1 },
+                "dependencies": { "react": "^18" },
+                "devDependencies": { "react": "^18", "typescript": "5" },
+                "workspaces": { "packages": ["packages/*"] },
+                "packageManager": "pnpm@9.4.0",
+                "engines": { "node": ">=18" },
+                "typings": "index.d.ts",
+                "bin": "cli.js"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.name.as_deref(), Some("@scope/tool"));
+        assert_eq!(manifest.version.as_deref(), Some("1.2.3"));
+        assert_eq!(
+            manifest.scripts,
+            BTreeMap::from([("test".to_string(), "mocha".to_string())])
+        );
+        assert_eq!(manifest.dependency_names(), vec!["react", "typescript"]);
+        assert!(manifest.has_dependency("typescript"));
+        assert_eq!(manifest.workspaces, vec!["packages/*"]);
+        assert_eq!(manifest.package_manager.as_deref(), Some("pnpm@9.4.0"));
+        assert_eq!(
+            manifest.engines.get("node").map(String::as_str),
+            Some(">=18")
+        );
+        assert_eq!(manifest.types.as_deref(), Some("index.d.ts"));
+        assert_eq!(
+            manifest.bin,
+            BTreeMap::from([("tool".to_string(), "cli.js".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let manifest = PackageManifest::parse(
+            r#"{ "name": 5, "scripts": [], "workspaces": ["a", 1], "bin": { "x": "x.js" } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.name, None);
+        assert!(manifest.scripts.is_empty());
+        assert_eq!(manifest.workspaces, vec!["a"]);
+        assert_eq!(manifest.bin.get("x").map(String::as_str), Some("x.js"));
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let error = PackageManifest::parse("{\n  \"name\": \"a\",\n}").unwrap_err();
+        assert!(error.to_string().contains("line 3 column 1"), "{error}");
+
+        let error = PackageManifest::parse("[]").unwrap_err();
+        assert!(error.to_string().contains("expected an object"), "{error}");
+    }
+
+    #[test]
+    fn test_has_types() {
+        let manifest = |json| PackageManifest::from_value(&json);
+
+        assert!(!manifest(serde_json::json!({ "main": "index.js" })).has_types());
+        assert!(manifest(serde_json::json!({ "types": "index.d.ts" })).has_types());
+        assert!(manifest(serde_json::json!({
+            "exports": { ".": { "types": "./index.d.ts", "default": "./index.js" } }
+        }))
+        .has_types());
+    }
+}
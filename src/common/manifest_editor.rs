@@ -0,0 +1,522 @@
+use eyre::{eyre, Context, ContextCompat, Result};
+use serde::Serialize;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::fs::Filesystem;
+use crate::manifest::PackageManifest;
+
+/// Edits package.json in place: only the changed value or the added/removed key is written, with indentation
+/// and line endings detected in the file. The rest of the file, like inline arrays or spacing inside objects,
+/// stays untouched
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestEditor {
+    contents: String,
+    value: serde_json::Value,
+    indent: String,
+    newline: &'static str,
+}
+
+/// Byte range of a JSON value in the file, with members when it's an object
+#[derive(Debug)]
+struct ValueSpan {
+    range: Range<usize>,
+    members: Option<Vec<MemberSpan>>,
+}
+
+#[derive(Debug)]
+struct MemberSpan {
+    key: String,
+    key_range: Range<usize>,
+    value: ValueSpan,
+}
+
+impl ValueSpan {
+    fn member(&self, key: &str) -> Option<&MemberSpan> {
+        self.members
+            .as_ref()?
+            .iter()
+            .find(|member| member.key == key)
+    }
+}
+
+impl ManifestEditor {
+    pub fn load(fs: &dyn Filesystem, path: &Path) -> Result<Self> {
+        let contents = fs
+            .read_to_string(path)
+            .with_context(|| format!("Couldn't read {path:?}"))?;
+
+        Self::parse(&contents).with_context(|| format!("Couldn't parse {path:?}"))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(contents)?;
+        if !value.is_object() {
+            return Err(eyre!("expected an object at line 1 column 1"));
+        }
+
+        Ok(Self {
+            contents: contents.to_string(),
+            value,
+            indent: detect_indent(contents),
+            newline: if contents.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            },
+        })
+    }
+
+    pub fn save(&self, fs: &dyn Filesystem, path: &Path) -> Result<()> {
+        fs.write(path, &self.contents)
+            .with_context(|| format!("Couldn't write {path:?}"))
+    }
+
+    pub fn manifest(&self) -> PackageManifest {
+        PackageManifest::from_value(&self.value)
+    }
+
+    /// Value under nested keys ex. `["scripts", "build"]`
+    pub fn get(&self, keys: &[&str]) -> Option<&serde_json::Value> {
+        keys.iter()
+            .try_fold(&self.value, |value, key| value.as_object()?.get(*key))
+    }
+
+    /// Replaces value in place or appends the key at the end of its object. Missing parents are created
+    pub fn set(&mut self, keys: &[&str], value: serde_json::Value) -> Result<()> {
+        let (last, parents) = keys.split_last().ok_or_else(|| eyre!("Empty key"))?;
+        let root = self.root()?;
+        let mut object = &root;
+        for (i, key) in keys.iter().enumerate() {
+            if object.members.is_none() {
+                return Err(eyre!("{} is not an object", keys[..i].join(".")));
+            }
+            match object.member(key) {
+                Some(member) if i == parents.len() => {
+                    let indent = self
+                        .is_multiline(object)
+                        .then(|| line_indent(&self.contents, member.key_range.start).to_string());
+                    let formatted = self.format_value(&value, indent.as_deref())?;
+                    self.contents
+                        .replace_range(member.value.range.clone(), &formatted);
+                    break;
+                }
+                Some(member) => object = &member.value,
+                None => {
+                    // missing parents are added together with the value
+                    let nested = keys[i + 1..]
+                        .iter()
+                        .rev()
+                        .fold(value.clone(), |value, key| {
+                            serde_json::Value::Object(serde_json::Map::from_iter([(
+                                key.to_string(),
+                                value,
+                            )]))
+                        });
+                    self.insert_member(object, key, &nested)?;
+                    break;
+                }
+            }
+        }
+
+        let mut object = self.value.as_object_mut().unwrap();
+        for key in parents {
+            object = object
+                .entry(key.to_string())
+                .or_insert_with(|| serde_json::Value::Object(Default::default()))
+                .as_object_mut()
+                .context("parent is not an object")?;
+        }
+        object.insert(last.to_string(), value);
+
+        Ok(())
+    }
+
+    /// Removes the key keeping order of the remaining ones. Returns removed value
+    pub fn delete(&mut self, keys: &[&str]) -> Option<serde_json::Value> {
+        let (last, parents) = keys.split_last()?;
+        let root = self.root().ok()?;
+        let object = parents
+            .iter()
+            .try_fold(&root, |object, key| Some(&object.member(key)?.value))?;
+        let members = object.members.as_ref()?;
+        let index = members.iter().position(|member| member.key == *last)?;
+        // the member goes together with the separator in front of it, or after it when it's the first one
+        let removed = match (index, members.get(1)) {
+            (0, Some(next)) => members[0].key_range.start..next.key_range.start,
+            (0, None) => object.range.start + 1..object.range.end - 1,
+            (index, _) => members[index - 1].value.range.end..members[index].value.range.end,
+        };
+        self.contents.replace_range(removed, "");
+
+        let mut object = self.value.as_object_mut().unwrap();
+        for key in parents {
+            object = object.get_mut(*key)?.as_object_mut()?;
+        }
+
+        object.shift_remove(*last)
+    }
+
+    fn root(&self) -> Result<ValueSpan> {
+        Scanner {
+            text: &self.contents,
+            pos: 0,
+        }
+        .value()
+        .context("Couldn't parse package.json")
+    }
+
+    /// Appends the member at the end of the object, separated like its other members
+    fn insert_member(
+        &mut self,
+        object: &ValueSpan,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<()> {
+        let key = serde_json::to_string(key)?;
+        let members = object.members.as_deref().unwrap_or_default();
+
+        let (range, text) = match members {
+            [.., previous, last] if !self.is_multiline(object) => {
+                let between = &self.contents[previous.value.range.end..last.key_range.start];
+                let separator = &between[between.find(',').map_or(0, |i| i + 1)..];
+                let colon = &self.contents[last.key_range.end..last.value.range.start];
+                let value = self.format_value(value, None)?;
+                let end = last.value.range.end;
+                (end..end, format!(",{separator}{key}{colon}{value}"))
+            }
+            [.., last] => {
+                let indent = self
+                    .is_multiline(object)
+                    .then(|| line_indent(&self.contents, last.key_range.start));
+                let colon = &self.contents[last.key_range.end..last.value.range.start];
+                // inline objects are spaced like after the colon
+                let separator = match indent {
+                    Some(indent) => format!("{}{indent}", self.newline),
+                    None if colon.ends_with(char::is_whitespace) => " ".to_string(),
+                    None => String::new(),
+                };
+                let value = self.format_value(value, indent)?;
+                let end = last.value.range.end;
+                (end..end, format!(",{separator}{key}{colon}{value}"))
+            }
+            [] if self.indent.is_empty() => {
+                let value = self.format_value(value, None)?;
+                (
+                    object.range.start + 1..object.range.end - 1,
+                    format!("{key}:{value}"),
+                )
+            }
+            [] => {
+                let object_indent = line_indent(&self.contents, object.range.start);
+                let indent = format!("{object_indent}{}", self.indent);
+                let value = self.format_value(value, Some(&indent))?;
+                (
+                    object.range.start + 1..object.range.end - 1,
+                    format!(
+                        "{newline}{indent}{key}: {value}{newline}{object_indent}",
+                        newline = self.newline
+                    ),
+                )
+            }
+        };
+        self.contents.replace_range(range, &text);
+
+        Ok(())
+    }
+
+    /// Object's members are on separate lines
+    fn is_multiline(&self, object: &ValueSpan) -> bool {
+        !self.indent.is_empty() && self.contents[object.range.clone()].contains('\n')
+    }
+
+    /// Pretty prints value of a member indented with `indent`, without it the value is kept in a single line
+    fn format_value(&self, value: &serde_json::Value, indent: Option<&str>) -> Result<String> {
+        let Some(indent) = indent else {
+            return Ok(value.to_string());
+        };
+        let mut out = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent.as_bytes());
+        value.serialize(&mut serde_json::Serializer::with_formatter(
+            &mut out, formatter,
+        ))?;
+
+        Ok(String::from_utf8(out)?.replace('\n', &format!("{}{indent}", self.newline)))
+    }
+}
+
+impl std::fmt::Display for ManifestEditor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.contents)
+    }
+}
+
+/// Finds spans of values in JSON already validated by serde_json
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn value(&mut self) -> Option<ValueSpan> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let members = match self.peek()? {
+            b'{' => Some(self.object()?),
+            b'[' => {
+                self.array()?;
+                None
+            }
+            b'"' => {
+                self.string()?;
+                None
+            }
+            _ => {
+                while self
+                    .peek()
+                    .is_some_and(|c| !matches!(c, b',' | b'}' | b']') && !c.is_ascii_whitespace())
+                {
+                    self.pos += 1;
+                }
+                None
+            }
+        };
+
+        Some(ValueSpan {
+            range: start..self.pos,
+            members,
+        })
+    }
+
+    fn object(&mut self) -> Option<Vec<MemberSpan>> {
+        self.pos += 1;
+        let mut members = vec![];
+        self.skip_whitespace();
+        if self.peek()? == b'}' {
+            self.pos += 1;
+            return Some(members);
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key_start = self.pos;
+            let key = self.string()?;
+            let key_range = key_start..self.pos;
+            self.skip_whitespace();
+            if self.bump()? != b':' {
+                return None;
+            }
+            let value = self.value()?;
+            members.push(MemberSpan {
+                key,
+                key_range,
+                value,
+            });
+            self.skip_whitespace();
+            match self.bump()? {
+                b',' => {}
+                b'}' => return Some(members),
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self) -> Option<()> {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek()? == b']' {
+            self.pos += 1;
+            return Some(());
+        }
+
+        loop {
+            self.value()?;
+            self.skip_whitespace();
+            match self.bump()? {
+                b',' => {}
+                b']' => return Some(()),
+                _ => return None,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        if self.bump()? != b'"' {
+            return None;
+        }
+        loop {
+            match self.bump()? {
+                b'\\' => self.pos += 1,
+                b'"' => break,
+                _ => {}
+            }
+        }
+
+        serde_json::from_str(&self.text[start..self.pos]).ok()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+}
+
+/// Whitespace at the beginning of the line containing `pos`
+fn line_indent(contents: &str, pos: usize) -> &str {
+    let line = &contents[contents[..pos].rfind('\n').map_or(0, |i| i + 1)..];
+    let end = line
+        .find(|c: char| c != ' ' && c != '\t')
+        .unwrap_or(line.len());
+
+    &line[..end]
+}
+
+/// Whitespace in front of the first indented line. Empty for minified files
+fn detect_indent(contents: &str) -> String {
+    contents
+        .lines()
+        .skip(1)
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::fs::{
+        test_utils::{expect_file, expect_write},
+        MockFilesystem,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_set_preserves_formatting() {
+        let mut editor = ManifestEditor::parse(
+            "{\r\n    \"name\": \"a\",\r\n    \"scripts\": {\r\n        \"test\": \"mocha\"\r\n    },\r\n    \"version\": \"1.0.0\"\r\n}\r\n",
+        )
+        .unwrap();
+
+        editor.set(&["scripts", "build"], json!("tsc")).unwrap();
+        editor.set(&["name"], json!("b")).unwrap();
+
+        assert_eq!(
+            editor.to_string(),
+            "{\r\n    \"name\": \"b\",\r\n    \"scripts\": {\r\n        \"test\": \"mocha\",\r\n        \"build\": \"tsc\"\r\n    },\r\n    \"version\": \"1.0.0\"\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_set_creates_parents() {
+        let mut editor = ManifestEditor::parse("{\n\t\"name\": \"a\"\n}").unwrap();
+
+        editor.set(&["engines", "node"], json!(">=18")).unwrap();
+
+        assert_eq!(
+            editor.to_string(),
+            "{\n\t\"name\": \"a\",\n\t\"engines\": {\n\t\t\"node\": \">=18\"\n\t}\n}"
+        );
+        assert!(editor.set(&["name", "first"], json!("x")).is_err());
+    }
+
+    #[test]
+    fn test_delete_keeps_order() {
+        let mut editor =
+            ManifestEditor::parse(r#"{"a": 1, "b": {"c": 2, "d": 3}, "e": 4}"#).unwrap();
+
+        assert_eq!(editor.delete(&["b", "c"]), Some(json!(2)));
+        assert_eq!(editor.delete(&["a"]), Some(json!(1)));
+        assert_eq!(editor.delete(&["x", "y"]), None);
+
+        assert_eq!(editor.get(&["b", "d"]), Some(&json!(3)));
+        assert_eq!(editor.to_string(), r#"{"b": {"d": 3}, "e": 4}"#);
+    }
+
+    #[test]
+    fn test_edits_keep_untouched_formatting() {
+        let mut editor = ManifestEditor::parse(
+            r#"{
+  "name": "a",
+  "files": ["dist"],
+  "author": {"name": "me"},
+  "keywords": [ "cli" ],
+  "scripts": {
+    "build": "tsc"
+  }
+}
+"#,
+        )
+        .unwrap();
+
+        editor.set(&["scripts", "test"], json!("vitest")).unwrap();
+        editor
+            .set(&["author", "url"], json!("https://me.dev"))
+            .unwrap();
+        editor.set(&["version"], json!("1.0.0")).unwrap();
+        editor.delete(&["keywords"]);
+
+        assert_eq!(
+            editor.to_string(),
+            r#"{
+  "name": "a",
+  "files": ["dist"],
+  "author": {"name": "me", "url": "https://me.dev"},
+  "scripts": {
+    "build": "tsc",
+    "test": "vitest"
+  },
+  "version": "1.0.0"
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_set_into_empty_object() {
+        let mut editor = ManifestEditor::parse("{\n  \"scripts\": {}\n}").unwrap();
+
+        editor.set(&["scripts", "build"], json!("tsc")).unwrap();
+
+        assert_eq!(
+            editor.to_string(),
+            "{\n  \"scripts\": {\n    \"build\": \"tsc\"\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let mut mock_fs = MockFilesystem::new();
+        let path = Path::new("/project/package.json");
+        expect_file(
+            &mut mock_fs,
+            path.to_owned(),
+            "{\n  \"name\": \"a\"\n}\n".to_owned(),
+        );
+        expect_write(
+            &mut mock_fs,
+            path.to_owned(),
+            "{\n  \"name\": \"a\",\n  \"private\": true\n}\n".to_owned(),
+        );
+
+        let mut editor = ManifestEditor::load(&mock_fs, path).unwrap();
+        editor.set(&["private"], json!(true)).unwrap();
+        editor.save(&mock_fs, path).unwrap();
+
+        assert_eq!(editor.manifest().name.as_deref(), Some("a"));
+    }
+}
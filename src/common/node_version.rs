@@ -0,0 +1,338 @@
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+use crate::execute::Executor;
+use crate::fs::{find_in_project, Filesystem};
+use crate::manifest::PackageManifest;
+use crate::semver::{Version, VersionReq};
+
+/// Node.js version required by the project
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRequirement {
+    /// where the requirement comes from ex. ".nvmrc"
+    pub source: &'static str,
+    pub range: String,
+    pub req: VersionReq,
+}
+
+/// Requirements from package.json's `volta.node`, `.nvmrc`, `.node-version` and `engines.node`. Version files
+/// are looked up to the project root. Aliases like "lts/*" can't be verified without a version manager so they
+/// are skipped.
+pub fn find_node_requirements(
+    fs: &dyn Filesystem,
+    project_dir: &Path,
+    manifest: Option<&PackageManifest>,
+) -> Vec<NodeRequirement> {
+    let version_file = |filename: &str| {
+        find_in_project(fs, project_dir, filename)
+            .and_then(|path| fs.read_to_string(&path).ok())
+            .and_then(|contents| contents.lines().next().map(|line| line.trim().to_string()))
+    };
+
+    [
+        (
+            "volta.node",
+            manifest.and_then(|manifest| manifest.volta.get("node").cloned()),
+        ),
+        (".nvmrc", version_file(".nvmrc")),
+        (".node-version", version_file(".node-version")),
+        (
+            "engines.node",
+            manifest.and_then(|manifest| manifest.engines.get("node").cloned()),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(source, range)| {
+        let range = range?;
+        let req = VersionReq::parse(&range)?;
+        Some(NodeRequirement { source, range, req })
+    })
+    .collect()
+}
+
+/// Directories of local Node.js version managers
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VersionManagers {
+    pub nvm: Option<PathBuf>,
+    pub fnm: Option<PathBuf>,
+    pub volta: Option<PathBuf>,
+}
+
+impl VersionManagers {
+    pub fn from_env() -> Self {
+        let env_dir = |name: &str| std::env::var_os(name).map(PathBuf::from);
+        let home = env_dir("HOME");
+
+        Self {
+            nvm: env_dir("NVM_DIR").or_else(|| home.as_ref().map(|home| home.join(".nvm"))),
+            fnm: env_dir("FNM_DIR")
+                .or_else(|| env_dir("XDG_DATA_HOME").map(|data| data.join("fnm")))
+                .or_else(|| home.as_ref().map(|home| home.join(".local/share/fnm"))),
+            volta: env_dir("VOLTA_HOME").or_else(|| home.as_ref().map(|home| home.join(".volta"))),
+        }
+    }
+
+    /// All installed Node.js versions as (version manager, version, bin directory)
+    pub fn installed_versions(&self, fs: &dyn Filesystem) -> Vec<(&'static str, Version, PathBuf)> {
+        let layouts = [
+            ("nvm", self.nvm.as_ref(), "versions/node", "bin"),
+            (
+                "fnm",
+                self.fnm.as_ref(),
+                "node-versions",
+                "installation/bin",
+            ),
+            ("volta", self.volta.as_ref(), "tools/image/node", "bin"),
+        ];
+
+        let mut versions = vec![];
+        for (manager, dir, versions_dir, bin_dir) in layouts {
+            let Some(versions_dir) = dir.map(|dir| dir.join(versions_dir)) else {
+                continue;
+            };
+            for name in fs.read_dir(&versions_dir).unwrap_or_default() {
+                if let Some(version) = Version::parse(&name) {
+                    versions.push((manager, version, versions_dir.join(&name).join(bin_dir)));
+                }
+            }
+        }
+
+        versions
+    }
+}
+
+/// Checks if active node satisfies project's requirements. When it doesn't, returns bin directory of the newest
+/// matching installation from a version manager or prints out a warning when there is none
+pub fn resolve_node_bin(
+    executor: &dyn Executor,
+    fs: &dyn Filesystem,
+    managers: &VersionManagers,
+    requirements: &[NodeRequirement],
+) -> Option<PathBuf> {
+    if requirements.is_empty() {
+        return None;
+    }
+    let satisfies = |version: &Version| requirements.iter().all(|r| r.req.matches(version));
+
+    let active_version = executor
+        .output("node", &["--version"])
+        .ok()
+        .and_then(|output| Version::parse(&output));
+    if active_version.as_ref().is_some_and(satisfies) {
+        return None;
+    }
+
+    let sources = requirements
+        .iter()
+        .map(|r| format!("{}: {}", r.source, r.range))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let installation = managers
+        .installed_versions(fs)
+        .into_iter()
+        .filter(|(_, version, _)| satisfies(version))
+        .max_by(|(_, a, _), (_, b, _)| a.cmp(b));
+
+    match installation {
+        Some((manager, version, bin_dir)) => {
+            println!(
+                "{}",
+                format!("Using Node.js {version} from {manager} ({sources})").dimmed()
+            );
+            Some(bin_dir)
+        }
+        None => {
+            let active = active_version
+                .map(|version| format!("Node.js {version}"))
+                .unwrap_or_else(|| "Node.js".to_string());
+            println!(
+                "{}",
+                format!("{active} doesn't satisfy {sources}. Install matching version with nvm, fnm or volta")
+                    .yellow()
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+    use mockall::predicate::eq;
+
+    use crate::execute::MockExecutor;
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+
+    use super::*;
+
+    fn requirement(source: &'static str, range: &str) -> NodeRequirement {
+        NodeRequirement {
+            source,
+            range: range.to_string(),
+            req: VersionReq::parse(range).unwrap(),
+        }
+    }
+
+    fn expect_active_node(mock_executor: &mut MockExecutor, version: &'static str) {
+        mock_executor
+            .expect_output()
+            .withf(|program, args| program == "node" && args == ["--version"])
+            .returning(move |_, _| Ok(version.to_string()));
+    }
+
+    #[test]
+    fn test_find_node_requirements() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/.nvmrc").to_owned(),
+            "v18\n".to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/.node-version").to_owned(),
+            "lts/iron\n".to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+        let manifest = PackageManifest::from_value(&serde_json::json!({
+            "engines": { "node": ">=18.10" },
+            "volta": { "node": "18.19.0" }
+        }));
+
+        assert_eq!(
+            find_node_requirements(&mock_fs, Path::new("/project"), Some(&manifest)),
+            vec![
+                requirement("volta.node", "18.19.0"),
+                requirement(".nvmrc", "v18"),
+                requirement("engines.node", ">=18.10"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_node_requirements_stops_at_project_root() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/.nvmrc").to_owned(),
+            "16\n".to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/projects/app/pnpm-lock.yaml").to_owned(),
+            String::new(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert_eq!(
+            find_node_requirements(&mock_fs, Path::new("/projects/app/packages/ui"), None),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_installed_versions() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs
+            .expect_read_dir()
+            .with(eq(Path::new("/home/.nvm/versions/node")))
+            .returning(|_| Ok(vec!["v18.19.0".to_string(), ".cache".to_string()]));
+        mock_fs
+            .expect_read_dir()
+            .with(eq(Path::new("/home/.fnm/node-versions")))
+            .returning(|_| Ok(vec!["v20.11.0".to_string()]));
+        mock_fs
+            .expect_read_dir()
+            .returning(|_| Err(eyre!("not found")));
+        let managers = VersionManagers {
+            nvm: Some(PathBuf::from("/home/.nvm")),
+            fnm: Some(PathBuf::from("/home/.fnm")),
+            volta: Some(PathBuf::from("/home/.volta")),
+        };
+
+        assert_eq!(
+            managers.installed_versions(&mock_fs),
+            vec![
+                (
+                    "nvm",
+                    Version::parse("18.19.0").unwrap(),
+                    PathBuf::from("/home/.nvm/versions/node/v18.19.0/bin")
+                ),
+                (
+                    "fnm",
+                    Version::parse("20.11.0").unwrap(),
+                    PathBuf::from("/home/.fnm/node-versions/v20.11.0/installation/bin")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_node_bin_active_satisfies() {
+        let mock_fs = MockFilesystem::new();
+        let mut mock_executor = MockExecutor::new();
+        expect_active_node(&mut mock_executor, "v20.11.0\n");
+
+        assert_eq!(
+            resolve_node_bin(
+                &mock_executor,
+                &mock_fs,
+                &VersionManagers::default(),
+                &[requirement("engines.node", ">=18")]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_node_bin_from_version_manager() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_read_dir().returning(|_| {
+            Ok(vec![
+                "v18.17.0".to_string(),
+                "v18.19.0".to_string(),
+                "v20.11.0".to_string(),
+            ])
+        });
+        let mut mock_executor = MockExecutor::new();
+        expect_active_node(&mut mock_executor, "v20.11.0\n");
+        let managers = VersionManagers {
+            nvm: Some(PathBuf::from("/home/.nvm")),
+            ..VersionManagers::default()
+        };
+
+        assert_eq!(
+            resolve_node_bin(
+                &mock_executor,
+                &mock_fs,
+                &managers,
+                &[requirement(".nvmrc", "18")]
+            ),
+            Some(PathBuf::from("/home/.nvm/versions/node/v18.19.0/bin"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_node_bin_not_installed() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs
+            .expect_read_dir()
+            .returning(|_| Ok(vec!["v20.11.0".to_string()]));
+        let mut mock_executor = MockExecutor::new();
+        expect_active_node(&mut mock_executor, "v20.11.0\n");
+        let managers = VersionManagers {
+            volta: Some(PathBuf::from("/home/.volta")),
+            ..VersionManagers::default()
+        };
+
+        assert_eq!(
+            resolve_node_bin(
+                &mock_executor,
+                &mock_fs,
+                &managers,
+                &[requirement(".nvmrc", "16")]
+            ),
+            None
+        );
+    }
+}
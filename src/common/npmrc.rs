@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::fs::{find_in_project, Filesystem};
+
+pub const NPM_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// Registries set in .npmrc files with `registry=` and `@scope:registry=`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Registries {
+    default: String,
+    scopes: HashMap<String, String>,
+}
+
+impl Default for Registries {
+    fn default() -> Self {
+        Self {
+            default: NPM_REGISTRY.to_string(),
+            scopes: HashMap::new(),
+        }
+    }
+}
+
+impl Registries {
+    /// Same as `load` with the home directory from HOME env var
+    pub fn from_env(fs: &dyn Filesystem, project_dir: &Path) -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        Self::load(fs, project_dir, home.as_deref())
+    }
+
+    /// Reads .npmrc of the project (looked up to the project root) and the one in `home`. Project settings win
+    pub fn load(fs: &dyn Filesystem, project_dir: &Path, home: Option<&Path>) -> Self {
+        let mut default = None;
+        let mut scopes = HashMap::new();
+        let files = [
+            find_in_project(fs, project_dir, ".npmrc"),
+            home.map(|home| home.join(".npmrc"))
+                .filter(|path| fs.exists(path)),
+        ];
+
+        for path in files.into_iter().flatten() {
+            let Ok(contents) = fs.read_to_string(&path) else {
+                continue;
+            };
+            for (key, value) in parse_npmrc(&contents) {
+                match key.strip_suffix(":registry") {
+                    Some(scope) if scope.starts_with('@') => {
+                        scopes.entry(scope.to_string()).or_insert(value);
+                    }
+                    _ if key == "registry" => {
+                        default.get_or_insert(value);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            default: default.unwrap_or_else(|| NPM_REGISTRY.to_string()),
+            scopes,
+        }
+    }
+
+    /// Registry serving the package, scoped packages can come from their own registry
+    pub fn registry_for(&self, package: &str) -> &str {
+        package
+            .split_once('/')
+            .and_then(|(scope, _)| self.scopes.get(scope))
+            .unwrap_or(&self.default)
+    }
+
+    /// Registry of unscoped packages
+    pub fn default_registry(&self) -> &str {
+        &self.default
+    }
+
+    pub fn is_npm(registry: &str) -> bool {
+        registry.trim_end_matches('/') == NPM_REGISTRY.trim_end_matches('/')
+    }
+
+    /// URL of the package metadata
+    pub fn package_url(&self, package: &str) -> String {
+        format!(
+            "{}/{package}",
+            self.registry_for(package).trim_end_matches('/')
+        )
+    }
+}
+
+/// `key=value` lines, comments start with `#` or `;`
+fn parse_npmrc(contents: &str) -> impl Iterator<Item = (&str, String)> {
+    contents.lines().filter_map(|line| {
+        let line = line.trim();
+        if line.starts_with(['#', ';']) {
+            return None;
+        }
+        let (key, value) = line.split_once('=')?;
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+            .unwrap_or(value);
+
+        Some((key.trim(), value.to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/project/.npmrc"),
+            "# company packages\n@acme:registry=https://npm.acme.dev/\n//npm.acme.dev/:_authToken=${TOKEN}\n"
+                .to_string(),
+        );
+        expect_file(
+            &mut mock_fs,
+            PathBuf::from("/home/.npmrc"),
+            "registry = \"https://mirror.dev/npm\"\n@acme:registry=https://other.dev\n".to_string(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        let registries =
+            Registries::load(&mock_fs, Path::new("/project"), Some(Path::new("/home")));
+
+        assert_eq!(
+            registries.package_url("@acme/ui"),
+            "https://npm.acme.dev/@acme/ui"
+        );
+        assert_eq!(registries.package_url("zod"), "https://mirror.dev/npm/zod");
+        assert_eq!(
+            registries.package_url("@types/node"),
+            "https://mirror.dev/npm/@types/node"
+        );
+        assert!(!Registries::is_npm(registries.registry_for("zod")));
+        assert!(Registries::is_npm(
+            Registries::default().registry_for("zod")
+        ));
+    }
+}
@@ -0,0 +1,396 @@
+use std::cmp::Ordering;
+
+// @note: minimal implementation of npm flavoured semver, just enough to compare versions coming from package.json files and the registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub prerelease: Option<String>,
+}
+
+impl Version {
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim().trim_start_matches(['v', '=']);
+        // build metadata doesn't take part in comparisons
+        let version = version.split('+').next()?;
+        let (core, prerelease) = match version.split_once('-') {
+            Some((core, prerelease)) => (core, Some(prerelease.to_string())),
+            None => (version, None),
+        };
+
+        let mut parts = core.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            major,
+            minor,
+            patch,
+            prerelease,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        self.prerelease.is_some()
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(prerelease) = &self.prerelease {
+            write!(f, "-{prerelease}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.prerelease, &other.prerelease) {
+                (None, None) => Ordering::Equal,
+                // a prerelease has lower precedence than the normal version
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_prerelease(a, b),
+            })
+    }
+}
+
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => {
+                let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => a.cmp(b),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// npm style version range ex. ">=18", "^20.1.0 || 22.x", "1.2 - 2"
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionReq {
+    // version satisfies requirement when it matches all comparators of any set
+    sets: Vec<Vec<Comparator>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+impl Comparator {
+    fn new(op: Op, version: Version) -> Self {
+        Self { op, version }
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        match self.op {
+            Op::Eq => version == &self.version,
+            Op::Gt => version > &self.version,
+            Op::Gte => version >= &self.version,
+            Op::Lt => version < &self.version,
+            Op::Lte => version <= &self.version,
+        }
+    }
+}
+
+// version with optional components, ex. "1", "1.2", "1.x"
+#[derive(Debug, Clone, Copy)]
+struct Partial<'a> {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    prerelease: Option<&'a str>,
+}
+
+impl<'a> Partial<'a> {
+    fn parse(version: &'a str) -> Option<Self> {
+        let version = version.trim_start_matches(['v', '=']);
+        let version = version.split('+').next()?;
+        let (core, prerelease) = match version.split_once('-') {
+            Some((core, prerelease)) => (core, Some(prerelease)),
+            None => (version, None),
+        };
+
+        let mut components = [None; 3];
+        for (i, part) in core.split('.').enumerate() {
+            if i >= 3 {
+                return None;
+            }
+            components[i] = match part {
+                "x" | "X" | "*" => None,
+                _ => Some(part.parse().ok()?),
+            };
+        }
+        // components after a wildcard are meaningless, ex. "1.x.3"
+        if components[0].is_none() {
+            components = [None; 3];
+        } else if components[1].is_none() {
+            components[2] = None;
+        }
+
+        Some(Self {
+            major: components[0],
+            minor: components[1],
+            patch: components[2],
+            prerelease,
+        })
+    }
+
+    fn floor(&self) -> Version {
+        Version {
+            major: self.major.unwrap_or(0),
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            prerelease: self.prerelease.map(|p| p.to_string()),
+        }
+    }
+
+    // smallest version that is not covered by this partial, ex. "1.2" -> "1.3.0-0"
+    fn ceiling(&self) -> Option<Version> {
+        match (self.major, self.minor, self.patch) {
+            (None, _, _) => None,
+            (Some(major), None, _) => Some(lowest(major + 1, 0, 0)),
+            (Some(major), Some(minor), None) => Some(lowest(major, minor + 1, 0)),
+            (Some(major), Some(minor), Some(patch)) => Some(lowest(major, minor, patch + 1)),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.patch.is_some()
+    }
+}
+
+// lowest possible version with given components (lower than any prerelease)
+fn lowest(major: u64, minor: u64, patch: u64) -> Version {
+    Version {
+        major,
+        minor,
+        patch,
+        prerelease: Some("0".to_string()),
+    }
+}
+
+impl VersionReq {
+    pub fn parse(range: &str) -> Option<Self> {
+        let sets = range
+            .split("||")
+            .map(parse_comparator_set)
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { sets })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.sets
+            .iter()
+            .any(|set| set.iter().all(|comparator| comparator.matches(version)))
+    }
+}
+
+fn parse_comparator_set(set: &str) -> Option<Vec<Comparator>> {
+    let set = set.trim();
+    if let Some((from, to)) = set.split_once(" - ") {
+        let from = Partial::parse(from.trim())?;
+        let to = Partial::parse(to.trim())?;
+        let mut comparators = vec![Comparator::new(Op::Gte, from.floor())];
+        if to.is_complete() {
+            comparators.push(Comparator::new(Op::Lte, to.floor()));
+        } else if let Some(ceiling) = to.ceiling() {
+            comparators.push(Comparator::new(Op::Lt, ceiling));
+        }
+        return Some(comparators);
+    }
+
+    // glue operators with versions, ex. ">= 1.2.3" -> ">=1.2.3"
+    let mut tokens: Vec<String> = vec![];
+    for token in set.split_whitespace() {
+        match tokens.last_mut() {
+            Some(last) if last.chars().all(|c| "<>=~^".contains(c)) => last.push_str(token),
+            _ => tokens.push(token.to_string()),
+        }
+    }
+
+    let mut comparators = vec![];
+    for token in tokens {
+        comparators.extend(parse_comparator(&token)?);
+    }
+    Some(comparators)
+}
+
+fn parse_comparator(token: &str) -> Option<Vec<Comparator>> {
+    let operator_len = token
+        .find(|c: char| !"<>=~^".contains(c))
+        .unwrap_or(token.len());
+    let (operator, version) = token.split_at(operator_len);
+    let partial = Partial::parse(version)?;
+    let floor = partial.floor();
+
+    let comparators = match (operator, partial.major, partial.minor) {
+        (_, None, _) if operator != "<" && operator != ">" => vec![],
+        ("" | "=", _, _) if partial.is_complete() => vec![Comparator::new(Op::Eq, floor)],
+        ("" | "=", _, _) => x_range(&partial),
+        ("^", Some(major), minor) => {
+            let ceiling = match (major, minor, partial.patch) {
+                (0, Some(0), Some(patch)) => lowest(0, 0, patch + 1),
+                (0, Some(minor), _) => lowest(0, minor + 1, 0),
+                (major, _, _) => lowest(major + 1, 0, 0),
+            };
+            vec![
+                Comparator::new(Op::Gte, floor),
+                Comparator::new(Op::Lt, ceiling),
+            ]
+        }
+        ("~" | "~>", Some(major), minor) => {
+            let ceiling = match minor {
+                Some(minor) => lowest(major, minor + 1, 0),
+                None => lowest(major + 1, 0, 0),
+            };
+            vec![
+                Comparator::new(Op::Gte, floor),
+                Comparator::new(Op::Lt, ceiling),
+            ]
+        }
+        (">=", _, _) => vec![Comparator::new(Op::Gte, floor)],
+        (">", _, _) if partial.is_complete() => vec![Comparator::new(Op::Gt, floor)],
+        (">", _, _) => match partial.ceiling() {
+            Some(ceiling) => vec![Comparator::new(Op::Gte, ceiling)],
+            // nothing is greater than "*"
+            None => vec![Comparator::new(Op::Lt, lowest(0, 0, 0))],
+        },
+        ("<", _, _) if partial.is_complete() => vec![Comparator::new(Op::Lt, floor)],
+        ("<", None, _) => vec![Comparator::new(Op::Lt, lowest(0, 0, 0))],
+        ("<", _, _) => vec![Comparator::new(
+            Op::Lt,
+            lowest(floor.major, floor.minor, floor.patch),
+        )],
+        ("<=", _, _) if partial.is_complete() => vec![Comparator::new(Op::Lte, floor)],
+        ("<=", _, _) => partial
+            .ceiling()
+            .map(|ceiling| vec![Comparator::new(Op::Lt, ceiling)])
+            .unwrap_or_default(),
+        _ => return None,
+    };
+
+    Some(comparators)
+}
+
+fn x_range(partial: &Partial) -> Vec<Comparator> {
+    match partial.ceiling() {
+        Some(ceiling) => vec![
+            Comparator::new(Op::Gte, partial.floor()),
+            Comparator::new(Op::Lt, ceiling),
+        ],
+        None => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Version::parse("18.2.0"),
+            Some(Version {
+                major: 18,
+                minor: 2,
+                patch: 0,
+                prerelease: None
+            })
+        );
+        assert_eq!(
+            Version::parse("v1.0.0-beta.1+build"),
+            Some(Version {
+                major: 1,
+                minor: 0,
+                patch: 0,
+                prerelease: Some("beta.1".to_string())
+            })
+        );
+        assert_eq!(Version::parse("1.0"), None);
+        assert_eq!(Version::parse("latest"), None);
+    }
+
+    #[test]
+    fn test_ordering() {
+        let v = |s| Version::parse(s).unwrap();
+
+        assert!(v("18.2.0") < v("18.10.0"));
+        assert!(v("1.0.0-alpha") < v("1.0.0"));
+        assert!(v("1.0.0-alpha.2") < v("1.0.0-alpha.10"));
+        assert!(v("1.0.0-alpha") < v("1.0.0-beta"));
+    }
+
+    fn satisfies(range: &str, version: &str) -> bool {
+        VersionReq::parse(range)
+            .unwrap()
+            .matches(&Version::parse(version).unwrap())
+    }
+
+    #[test]
+    fn test_version_req() {
+        assert!(satisfies(">=18", "20.11.0"));
+        assert!(!satisfies(">=18", "16.20.2"));
+        assert!(satisfies(">= 18.0.0 < 21", "20.0.0"));
+        assert!(!satisfies(">= 18.0.0 < 21", "21.0.0"));
+        assert!(satisfies("^18.12 || ^20.9", "20.11.0"));
+        assert!(!satisfies("^18.12 || ^20.9", "19.0.0"));
+        assert!(satisfies("^0.2.3", "0.2.9"));
+        assert!(!satisfies("^0.2.3", "0.3.0"));
+        assert!(satisfies("~1.2", "1.2.9"));
+        assert!(!satisfies("~1.2", "1.3.0"));
+        assert!(satisfies("18.x", "18.19.1"));
+        assert!(satisfies("20", "20.1.0"));
+        assert!(!satisfies("20", "21.0.0"));
+        assert!(satisfies("1.2 - 2.3", "2.3.5"));
+        assert!(!satisfies("1.2 - 2.3", "2.4.0"));
+        assert!(satisfies("*", "1.0.0"));
+        assert!(satisfies("", "1.0.0"));
+        assert!(satisfies("v9.4.0", "9.4.0"));
+        assert!(!satisfies("9.4.0", "9.4.1"));
+        assert!(satisfies("<=1.2", "1.2.7"));
+        assert!(!satisfies("<1.2", "1.2.0"));
+        assert!(!satisfies(">1.2", "1.2.7"));
+        assert!(satisfies(">1.2", "1.3.0"));
+        assert!(VersionReq::parse("lts/*").is_none());
+    }
+}
@@ -0,0 +1,320 @@
+use eyre::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::fs::{find_in_parents, is_project_root, Filesystem};
+
+const CONFIG_FILES: &[&str] = &["tsconfig.json", "jsconfig.json"];
+const MAX_EXTENDS_DEPTH: usize = 16;
+
+/// Finds the closest tsconfig.json or jsconfig.json. Lookup stops at the project root (directory with a lockfile or .git)
+/// so configs of unrelated projects placed higher in the directory tree are ignored.
+pub fn find_tsconfig(fs: &dyn Filesystem, cwd: &Path) -> Option<PathBuf> {
+    let mut dir = Some(cwd);
+    while let Some(current_dir) = dir {
+        for config_file in CONFIG_FILES {
+            let path = current_dir.join(config_file);
+            if fs.exists(&path) {
+                return Some(path);
+            }
+        }
+        if is_project_root(fs, current_dir) {
+            return None;
+        }
+        dir = current_dir.parent();
+    }
+
+    None
+}
+
+/// Loads tsconfig resolving `extends` chain. Configs that can't be resolved are skipped.
+pub fn load_tsconfig(fs: &dyn Filesystem, path: &Path) -> Result<serde_json::Value> {
+    load_tsconfig_with_depth(fs, path, 0)
+}
+
+fn load_tsconfig_with_depth(
+    fs: &dyn Filesystem,
+    path: &Path,
+    depth: usize,
+) -> Result<serde_json::Value> {
+    if depth > MAX_EXTENDS_DEPTH {
+        bail!("Too deep \"extends\" chain in {path:?}");
+    }
+
+    let contents = fs.read_to_string(path)?;
+    let mut config = parse_jsonc(&contents).with_context(|| format!("Couldn't parse {path:?}"))?;
+    let extends = match config.get("extends") {
+        Some(serde_json::Value::String(extends)) => vec![extends.clone()],
+        Some(serde_json::Value::Array(extends)) => extends
+            .iter()
+            .filter_map(|e| e.as_str())
+            .map(|e| e.to_string())
+            .collect(),
+        _ => vec![],
+    };
+    if let Some(config) = config.as_object_mut() {
+        config.remove("extends");
+    }
+
+    let config_dir = path.parent().unwrap_or(Path::new("/"));
+    let mut merged = serde_json::json!({});
+    for extends in extends {
+        if let Some(base_path) = resolve_extends(fs, config_dir, &extends) {
+            merge_tsconfig(
+                &mut merged,
+                load_tsconfig_with_depth(fs, &base_path, depth + 1)?,
+            );
+        }
+    }
+    merge_tsconfig(&mut merged, config);
+
+    Ok(merged)
+}
+
+fn resolve_extends(fs: &dyn Filesystem, config_dir: &Path, extends: &str) -> Option<PathBuf> {
+    let mut candidates = vec![];
+    if extends.ends_with(".json") {
+        candidates.push(extends.to_string());
+    } else {
+        candidates.push(format!("{extends}.json"));
+        candidates.push(format!("{extends}/tsconfig.json"));
+    }
+
+    if extends.starts_with('.') || extends.starts_with('/') {
+        candidates
+            .into_iter()
+            .map(|candidate| config_dir.join(candidate))
+            .find(|path| fs.exists(path))
+    } else {
+        // shared config from a package ex. "@tsconfig/node18/tsconfig.json"
+        candidates.into_iter().find_map(|candidate| {
+            let sub_path = Path::new("node_modules").join(candidate);
+            find_in_parents(fs, config_dir, sub_path.to_str()?)
+        })
+    }
+}
+
+// options from extending config override these from the base, other keys are replaced
+fn merge_tsconfig(base: &mut serde_json::Value, config: serde_json::Value) {
+    let (Some(base), serde_json::Value::Object(config)) = (base.as_object_mut(), config) else {
+        return;
+    };
+
+    for (key, value) in config {
+        match (base.get_mut(&key), value) {
+            (Some(serde_json::Value::Object(base_options)), serde_json::Value::Object(options))
+                if key == "compilerOptions" =>
+            {
+                base_options.extend(options)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Returns `compilerOptions.<key>` as a list of lowercased strings
+pub fn compiler_option_list(tsconfig: &serde_json::Value, key: &str) -> Option<Vec<String>> {
+    tsconfig
+        .get("compilerOptions")
+        .and_then(|options| options.get(key))
+        .and_then(|values| values.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|v| v.to_lowercase())
+                .collect()
+        })
+}
+
+/// tsconfig.json (as many other JS tools configs) allows comments and trailing commas
+pub fn parse_jsonc(contents: &str) -> Result<serde_json::Value> {
+    Ok(serde_json::from_str(&strip_jsonc(contents))?)
+}
+
+fn strip_jsonc(contents: &str) -> String {
+    strip_trailing_commas(&strip_comments(contents))
+}
+
+fn strip_comments(contents: &str) -> String {
+    let mut output = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        output.push(c);
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+fn strip_trailing_commas(contents: &str) -> String {
+    let mut output = String::with_capacity(contents.len());
+    let mut chars = contents.chars();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                output.push(c);
+            }
+            ',' => {
+                let next = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(next, Some('}') | Some(']')) {
+                    output.push(c);
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::{test_utils::expect_file, MockFilesystem};
+
+    use super::*;
+
+    #[test]
+    fn test_find_tsconfig_stops_at_project_root() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|path| {
+            path == Path::new("/project/package-lock.json") || path == Path::new("/tsconfig.json")
+        });
+
+        assert_eq!(find_tsconfig(&mock_fs, Path::new("/project/nested")), None);
+    }
+
+    #[test]
+    fn test_find_tsconfig_jsconfig() {
+        let mut mock_fs = MockFilesystem::new();
+        mock_fs.expect_exists().returning(|path| {
+            path == Path::new("/project/package-lock.json")
+                || path == Path::new("/project/jsconfig.json")
+        });
+
+        assert_eq!(
+            find_tsconfig(&mock_fs, Path::new("/project/nested")),
+            Some(PathBuf::from("/project/jsconfig.json"))
+        );
+    }
+
+    #[test]
+    fn test_load_tsconfig_extends() {
+        let mut mock_fs = MockFilesystem::new();
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/tsconfig.json").to_owned(),
+            r#"{
+                "extends": ["@tsconfig/node18", "./tsconfig.base"],
+                "compilerOptions": { "strict": true }
+            }"#
+            .to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/project/tsconfig.base.json").to_owned(),
+            r#"{ "compilerOptions": { "strict": false, "types": ["node"] }, "include": ["src"] }"#
+                .to_owned(),
+        );
+        expect_file(
+            &mut mock_fs,
+            Path::new("/node_modules/@tsconfig/node18/tsconfig.json").to_owned(),
+            r#"{ "compilerOptions": { "lib": ["es2023"], "types": [] } }"#.to_owned(),
+        );
+        mock_fs.expect_exists().returning(|_| false);
+
+        assert_eq!(
+            load_tsconfig(&mock_fs, Path::new("/project/tsconfig.json")).unwrap(),
+            serde_json::json!({
+                "compilerOptions": { "lib": ["es2023"], "strict": true, "types": ["node"] },
+                "include": ["src"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_jsonc() {
+        let parsed = parse_jsonc(
+            r#"{
+                // comment
+                "compilerOptions": {
+                    /* multi
+                       line */
+                    "types": ["node",],
+                    "paths": { "@/*": ["./src/*"] }, // comment-like string above
+                },
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            parsed,
+            serde_json::json!({
+                "compilerOptions": {
+                    "types": ["node"],
+                    "paths": { "@/*": ["./src/*"] }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_compiler_option_list() {
+        let tsconfig = serde_json::json!({ "compilerOptions": { "lib": ["ES2020", "DOM"] } });
+
+        assert_eq!(
+            compiler_option_list(&tsconfig, "lib"),
+            Some(vec!["es2020".to_string(), "dom".to_string()])
+        );
+        assert_eq!(compiler_option_list(&tsconfig, "types"), None);
+    }
+}
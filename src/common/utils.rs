@@ -5,3 +5,12 @@ macro_rules! vec_of_strings {
 
 #[cfg(test)]
 pub(crate) use vec_of_strings;
+
+/// Parses boolean-ish values commonly used in env vars ex. "1", "true", "off"
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
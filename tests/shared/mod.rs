@@ -1,6 +1,6 @@
 use common::agent::Agent;
 use serde_json::{Map, Value};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 #[allow(dead_code)]
@@ -35,10 +35,9 @@ pub fn bash(cwd: &PathBuf, cmd: &str) -> String {
         .expect("Failed to execute command");
 
     if !output.status.success() {
-        assert!(
-            false,
+        panic!(
             "bash command did not exit successfully: {}",
-            String::from_utf8_lossy(&output.stderr).to_string()
+            String::from_utf8_lossy(&output.stderr)
         );
     }
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
@@ -54,7 +53,7 @@ pub fn get_debug_dir() -> PathBuf {
 }
 
 #[allow(dead_code)]
-pub fn assert_package_json_dependency(cwd: &PathBuf, expected_dep: &str, dev: bool) {
+pub fn assert_package_json_dependency(cwd: &Path, expected_dep: &str, dev: bool) {
     let path = cwd.join("package.json");
     let manifest_raw = std::fs::read_to_string(path).unwrap();
     let manifest: serde_json::Value = serde_json::from_str(&manifest_raw).unwrap();
@@ -71,7 +70,7 @@ pub fn assert_package_json_dependency(cwd: &PathBuf, expected_dep: &str, dev: bo
 }
 
 #[allow(dead_code)]
-pub fn insert_npm_scripts(cwd: &PathBuf, scripts: &[(&str, &str)]) {
+pub fn insert_npm_scripts(cwd: &Path, scripts: &[(&str, &str)]) {
     let path = cwd.join("package.json");
     let manifest_raw = std::fs::read_to_string(&path).unwrap();
     let mut manifest: serde_json::Value = serde_json::from_str(&manifest_raw).unwrap();
@@ -14,7 +14,7 @@ fn test_add_pure_js_dependency() -> Result<(), io::Error> {
         );
         let tmp_dir = TempDir::new(shared::agent_as_str(agent))?;
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
 
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
         let stdout = shared::bash(&cwd, "ny add repeat-string");
@@ -39,7 +39,7 @@ fn test_add_typescript_dependency() -> Result<(), io::Error> {
         );
         let tmp_dir = TempDir::new(shared::agent_as_str(agent))?;
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
         shared::bash(&cwd, "ny add typescript");
         shared::bash(&cwd, "ny run tsc --init");
@@ -52,3 +52,25 @@ fn test_add_typescript_dependency() -> Result<(), io::Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_dry_run_without_agent_in_path() -> Result<(), io::Error> {
+    let tmp_dir = TempDir::new("pnpm")?;
+    let cwd = tmp_dir.into_path();
+    std::fs::write(cwd.join("package.json"), r#"{"name": "app"}"#)?;
+    std::fs::write(cwd.join("pnpm-lock.yaml"), "lockfileVersion: '9.0'\n")?;
+
+    for args in [&["--dry-run", "install"][..], &["--dry-run", "add", "zod"]] {
+        // only ny is in PATH, pnpm isn't installed
+        let output = std::process::Command::new(shared::get_debug_dir().join("ny"))
+            .args(args)
+            .env("PATH", shared::get_debug_dir())
+            .current_dir(&cwd)
+            .output()?;
+
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains("pnpm"));
+    }
+
+    Ok(())
+}
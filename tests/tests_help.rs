@@ -9,7 +9,7 @@ fn test_help_works() {
     let cwd = tmp_dir.into_path();
 
     let output = shared::bash(&cwd, "ny --help");
-    assert!(output.contains("Usage: ny [COMMAND]"));
+    assert!(output.contains("Usage: ny [OPTIONS] [COMMAND]"));
 }
 
 #[test]
@@ -30,7 +30,7 @@ fn test_help_run_echo_cli_help_prints_echo_help() {
         );
         let tmp_dir = TempDir::new(shared::agent_as_str(agent)).unwrap();
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
         shared::bash(&cwd, "ny add echo-cli");
 
@@ -48,7 +48,7 @@ fn test_help_run_echo_cli_some_help_passes_all_args() {
         );
         let tmp_dir = TempDir::new(shared::agent_as_str(agent)).unwrap();
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
         shared::bash(&cwd, "ny add echo-cli");
 
@@ -66,7 +66,7 @@ fn test_help_echo_cli_help_prints_echo_help() {
         );
         let tmp_dir = TempDir::new(shared::agent_as_str(agent)).unwrap();
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
         shared::bash(&cwd, "ny add echo-cli");
 
@@ -84,7 +84,7 @@ fn test_help_echo_cli_some_help_passes_all_args() {
         );
         let tmp_dir = TempDir::new(shared::agent_as_str(agent)).unwrap();
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
         shared::bash(&cwd, "ny add echo-cli");
 
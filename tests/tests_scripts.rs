@@ -8,9 +8,9 @@ fn test_scripts_shell_script() -> Result<(), io::Error> {
     for agent in shared::all_agents() {
         let tmp_dir = TempDir::new("npm")?;
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
 
-        shared::bash(&cwd, shared::agent_to_init_command(&agent).as_str());
+        shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
 
         shared::insert_npm_scripts(
             &cwd,
@@ -33,7 +33,7 @@ fn test_scripts_shell_script2() -> Result<(), io::Error> {
     for agent in shared::all_agents() {
         let tmp_dir = TempDir::new("npm")?;
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
 
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
 
@@ -51,7 +51,7 @@ fn test_scripts_npm_script() -> Result<(), io::Error> {
     for agent in shared::all_agents() {
         let tmp_dir = TempDir::new("npm")?;
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
 
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
         shared::bash(&cwd, "ny add echo-cli"); // required dep by some scripts
@@ -81,7 +81,7 @@ fn test_scripts_passing_extra_args() -> Result<(), io::Error> {
     for agent in shared::all_agents() {
         let tmp_dir = TempDir::new("npm")?;
         let cwd = tmp_dir.into_path();
-        println!("Working dir: {}", &cwd.display());
+        println!("Working dir: {}", cwd.display());
 
         shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
         shared::bash(&cwd, "ny add echo-cli"); // required dep by some scripts
@@ -97,3 +97,30 @@ fn test_scripts_passing_extra_args() -> Result<(), io::Error> {
 
     Ok(())
 }
+
+#[test]
+fn test_scripts_named_like_commands() -> Result<(), io::Error> {
+    for agent in shared::all_agents() {
+        let tmp_dir = TempDir::new("npm")?;
+        let cwd = tmp_dir.into_path();
+        println!("Working dir: {}", cwd.display());
+
+        shared::bash(&cwd, shared::agent_to_init_command(agent).as_str());
+
+        shared::insert_npm_scripts(
+            &cwd,
+            &[
+                ("outdated", "echo 'outdated-script-was-run'"),
+                ("doctor", "echo 'doctor-script-was-run'"),
+            ],
+        );
+
+        let outdated_output = shared::bash(&cwd, "ny outdated");
+        assert!(outdated_output.contains("outdated-script-was-run"));
+
+        let doctor_output = shared::bash(&cwd, "ny doctor");
+        assert!(doctor_output.contains("doctor-script-was-run"));
+    }
+
+    Ok(())
+}